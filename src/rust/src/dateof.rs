@@ -1,4 +1,4 @@
-use chrono::{Datelike, NaiveDate};
+use chrono::{Datelike, NaiveDate, Weekday};
 
 fn to_quarter(month: u32) -> i32 {
     match month {
@@ -33,6 +33,10 @@ make_fun!(quarter, |date: &NaiveDate| -> i32 {
     to_quarter(date.month())
 });
 
+make_fun!(isoyear, |date: &NaiveDate| -> i32 {
+    date.iso_week().year()
+});
+
 make_fun!(isoweek, |date: &NaiveDate| -> i32 {
     date.iso_week().week() as i32
 });
@@ -51,6 +55,20 @@ make_fun!(isowday, |date: &NaiveDate| -> i32 {
 
 // yday wday week yearmon yearqtr
 
+// the inverse of isoyear, isoweek and isowday
+pub fn from_isoweek(year: i32, week: u32, wday: u32) -> Option<NaiveDate> {
+    let wday = match wday {
+        1..=7 => Weekday::try_from((wday - 1) as u8).ok()?,
+        _ => return None,
+    };
+    NaiveDate::from_isoywd_opt(year, week, wday)
+}
+
+// the inverse of year and yday
+pub fn from_yday(year: i32, yday: u32) -> Option<NaiveDate> {
+    NaiveDate::from_yo_opt(year, yday)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -86,4 +104,34 @@ mod test {
         ];
         assert_eq!(wday(&dates), wday_expect);
     }
+
+    #[test]
+    fn isoweek_and_yday_round_trip() {
+        let date0 = NaiveDate::from_ymd_opt(2020, 12, 20).unwrap();
+        let dates: Vec<Option<NaiveDate>> = (0..=30)
+            .map(|i| NaiveDate::from_num_days_from_ce_opt(date0.num_days_from_ce() + i))
+            .collect();
+        let (y, w, d) = (isoyear(&dates), isoweek(&dates), isowday(&dates));
+        for i in 0..dates.len() {
+            let date = from_isoweek(y[i].unwrap(), w[i].unwrap() as u32, d[i].unwrap() as u32);
+            assert_eq!(date, dates[i]);
+        }
+        let (y, d) = (year(&dates), yday(&dates));
+        for i in 0..dates.len() {
+            assert_eq!(from_yday(y[i].unwrap(), d[i].unwrap() as u32), dates[i]);
+        }
+        assert_eq!(
+            from_isoweek(2021, 52, 6),
+            NaiveDate::from_ymd_opt(2022, 1, 1)
+        );
+        assert_eq!(from_isoweek(2021, 53, 1), None);
+        assert_eq!(
+            from_isoweek(2020, 53, 1),
+            NaiveDate::from_ymd_opt(2020, 12, 28)
+        );
+        assert_eq!(from_isoweek(2021, 1, 0), None);
+        assert_eq!(from_isoweek(2021, 1, 8), None);
+        assert_eq!(from_yday(2021, 366), None);
+        assert_eq!(from_yday(2020, 366), NaiveDate::from_ymd_opt(2020, 12, 31));
+    }
 }
@@ -0,0 +1,110 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::rdate::R_DATE_FROM_CE;
+
+// The serial 0 of the 1900 date system is 1899-12-30 for the serials after the
+// fake 1900-02-29 (serial 60), i.e., 25569 days before 1970-01-01
+const EXCEL_1900_FROM_CE: i32 = R_DATE_FROM_CE - 25569;
+// The serial 0 of the 1904 date system is 1904-01-01
+const EXCEL_1904_FROM_CE: i32 = R_DATE_FROM_CE - 24107;
+// 9999-12-31 is the largest date that Excel supports
+const MAX_SERIAL_1900: i32 = 2958465;
+const MAX_SERIAL_1904: i32 = 2957003;
+
+// the fractional part (the time of the day) is dropped
+pub fn serial2date(x: f64, date1904: bool) -> Option<NaiveDate> {
+    if !x.is_finite() {
+        return None;
+    }
+    let serial = x.floor() as i32;
+    let days = match (date1904, serial) {
+        (true, 0..=MAX_SERIAL_1904) => serial + EXCEL_1904_FROM_CE,
+        // Excel regards 1900 as a leap year so the serials before the fake
+        // 1900-02-29 are shifted by one day
+        (false, 1..=59) => serial + EXCEL_1900_FROM_CE + 1,
+        (false, 61..=MAX_SERIAL_1900) => serial + EXCEL_1900_FROM_CE,
+        _ => return None,
+    };
+    NaiveDate::from_num_days_from_ce_opt(days)
+}
+
+pub fn date2serial(x: &NaiveDate, date1904: bool) -> Option<i32> {
+    let days = x.num_days_from_ce();
+    if date1904 {
+        let serial = days - EXCEL_1904_FROM_CE;
+        (0..=MAX_SERIAL_1904).contains(&serial).then_some(serial)
+    } else {
+        match days - EXCEL_1900_FROM_CE {
+            serial @ 61..=MAX_SERIAL_1900 => Some(serial),
+            // 1900-01-01 to 1900-02-28
+            serial @ 2..=60 => Some(serial - 1),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn system_1900() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(serial2date(44555.0, false), fromymd(2021, 12, 25));
+        assert_eq!(serial2date(44555.75, false), fromymd(2021, 12, 25));
+        assert_eq!(serial2date(25569.0, false), fromymd(1970, 1, 1));
+        assert_eq!(serial2date(1.0, false), fromymd(1900, 1, 1));
+        assert_eq!(serial2date(59.0, false), fromymd(1900, 2, 28));
+        assert_eq!(serial2date(60.0, false), None);
+        assert_eq!(serial2date(61.0, false), fromymd(1900, 3, 1));
+        assert_eq!(serial2date(2958465.0, false), fromymd(9999, 12, 31));
+        assert_eq!(serial2date(2958466.0, false), None);
+        assert_eq!(serial2date(0.0, false), None);
+        assert_eq!(serial2date(-1.0, false), None);
+        assert_eq!(serial2date(f64::NAN, false), None);
+        assert_eq!(serial2date(f64::INFINITY, false), None);
+        assert_eq!(serial2date(1e20, false), None);
+
+        assert_eq!(
+            date2serial(&fromymd(2021, 12, 25).unwrap(), false),
+            Some(44555)
+        );
+        assert_eq!(date2serial(&fromymd(1900, 1, 1).unwrap(), false), Some(1));
+        assert_eq!(date2serial(&fromymd(1900, 2, 28).unwrap(), false), Some(59));
+        assert_eq!(date2serial(&fromymd(1900, 3, 1).unwrap(), false), Some(61));
+        assert_eq!(
+            date2serial(&fromymd(9999, 12, 31).unwrap(), false),
+            Some(2958465)
+        );
+        assert_eq!(date2serial(&fromymd(1899, 12, 31).unwrap(), false), None);
+        assert_eq!(date2serial(&fromymd(10000, 1, 1).unwrap(), false), None);
+    }
+
+    #[test]
+    fn system_1904() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(serial2date(43093.0, true), fromymd(2021, 12, 25));
+        assert_eq!(serial2date(0.0, true), fromymd(1904, 1, 1));
+        assert_eq!(serial2date(2957003.0, true), fromymd(9999, 12, 31));
+        assert_eq!(serial2date(-1.0, true), None);
+        assert_eq!(serial2date(2957004.0, true), None);
+
+        assert_eq!(
+            date2serial(&fromymd(2021, 12, 25).unwrap(), true),
+            Some(43093)
+        );
+        assert_eq!(date2serial(&fromymd(1904, 1, 1).unwrap(), true), Some(0));
+        assert_eq!(date2serial(&fromymd(1903, 12, 31).unwrap(), true), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        for serial in (1..=59).chain(61..=70000) {
+            let date = serial2date(serial as f64, false).unwrap();
+            assert_eq!(date2serial(&date, false), Some(serial));
+        }
+        for serial in 0..=70000 {
+            let date = serial2date(serial as f64, true).unwrap();
+            assert_eq!(date2serial(&date, true), Some(serial));
+        }
+    }
+}
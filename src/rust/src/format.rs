@@ -0,0 +1,118 @@
+use chrono::format::{parse, Item, Parsed, StrftimeItems};
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+use crate::parse::expand_year;
+
+// compiles the strptime-like format once so that it can be reused for every
+// element; returns None if the format is invalid
+pub fn compile(fmt: &str) -> Option<Vec<Item<'_>>> {
+    StrftimeItems::new(fmt).parse().ok()
+}
+
+// the whole string must match the format, otherwise it returns None
+pub fn strp2date(x: &str, items: &[Item], pivot: i32) -> Option<NaiveDate> {
+    let mut parsed = Parsed::new();
+    parse(&mut parsed, x, items.iter()).ok()?;
+    // `%y` alone follows the pivot instead of chrono's fixed 1970
+    if let (None, None, Some(y)) = (parsed.year(), parsed.year_div_100(), parsed.year_mod_100()) {
+        parsed.set_year(expand_year(y, pivot)? as i64).ok()?;
+    }
+    parsed.to_naive_date().ok()
+}
+
+// the other way from strp2date(); None if the format needs what a date doesn't
+// have, e.g., the hour `%H`
+pub fn date2strf(x: &NaiveDate, items: &[Item]) -> Option<String> {
+    let mut out = String::new();
+    write!(out, "{}", x.format_with_items(items.iter())).ok()?;
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::DEFAULT_PIVOT;
+    use chrono::NaiveDate;
+
+    fn strp(x: &str, fmt: &str, pivot: i32) -> Option<NaiveDate> {
+        strp2date(x, &compile(fmt).unwrap(), pivot)
+    }
+
+    #[test]
+    fn formats() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(
+            strp("20211225", "%Y%m%d", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            strp("25.12.21", "%d.%m.%y", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            strp("5.1.21", "%d.%m.%y", DEFAULT_PIVOT),
+            fromymd(2021, 1, 5)
+        );
+        assert_eq!(
+            strp("Dec 25, 2021", "%b %d, %Y", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            strp("DEC 25, 2021", "%b %d, %Y", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            strp("December 25 2021", "%B %d %Y", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            strp("2021-359", "%Y-%j", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            strp("2021-12-25 13:45:00", "%Y-%m-%d %H:%M:%S", DEFAULT_PIVOT),
+            fromymd(2021, 12, 25)
+        );
+    }
+
+    #[test]
+    fn two_digit_years() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(
+            strp("691225", "%y%m%d", DEFAULT_PIVOT),
+            fromymd(2069, 12, 25)
+        );
+        assert_eq!(
+            strp("701225", "%y%m%d", DEFAULT_PIVOT),
+            fromymd(1970, 12, 25)
+        );
+        assert_eq!(strp("651225", "%y%m%d", 1950), fromymd(1965, 12, 25));
+        assert_eq!(strp("721225", "%y%m%d", 2000), fromymd(2072, 12, 25));
+        // the century is given explicitly
+        assert_eq!(strp("19 651225", "%C %y%m%d", 2000), fromymd(1965, 12, 25));
+    }
+
+    #[test]
+    fn non_matching() {
+        assert_eq!(strp("2021-12-25", "%Y%m%d", DEFAULT_PIVOT), None);
+        assert_eq!(strp("2021-12-25x", "%Y-%m-%d", DEFAULT_PIVOT), None);
+        assert_eq!(strp("2021-02-30", "%Y-%m-%d", DEFAULT_PIVOT), None);
+        assert_eq!(strp("2021-12", "%Y-%m", DEFAULT_PIVOT), None);
+        assert_eq!(strp("Dex 25, 2021", "%b %d, %Y", DEFAULT_PIVOT), None);
+        assert!(compile("%Y-%Q").is_none());
+    }
+
+    #[test]
+    fn formatting() {
+        let date = NaiveDate::from_ymd_opt(2021, 7, 5).unwrap();
+        let strf = |fmt| date2strf(&date, &compile(fmt).unwrap());
+        assert_eq!(strf("%Y%m%d").as_deref(), Some("20210705"));
+        assert_eq!(strf("%Y-%m").as_deref(), Some("2021-07"));
+        assert_eq!(strf("%d/%m/%Y").as_deref(), Some("05/07/2021"));
+        assert_eq!(strf("%Y年%m月%d日").as_deref(), Some("2021年07月05日"));
+        assert_eq!(strf("%b %e, %Y").as_deref(), Some("Jul  5, 2021"));
+        assert_eq!(strf("%y%m%d").as_deref(), Some("210705"));
+        assert_eq!(strf("%Y-%m-%d %H:%M"), None);
+    }
+}
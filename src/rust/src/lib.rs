@@ -1,96 +1,715 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use extendr_api::prelude::*;
 mod dateof;
+mod excel;
+mod format;
+mod parse;
 mod rdate;
-use rdate::ToRDate;
+use parse::{DateTimeResult, Order, ParseResult, Reason};
 mod period;
 
-// short_year: when true, 980102 will be converted to 19980102; when false
-// it will be regarded as 00980102
-fn int2date(x: i32, short_year: bool) -> Option<NaiveDate> {
-    let day: i32 = x % 100;
-    let month: i32 = x / 100 % 100;
-    let y_part: i32 = x / 10000;
-    let year = if short_year && y_part < 100 {
-        // 700102 => 19700102; 980403 => 19980403; 691022 =? 20691022; 010204 => 20010204
-        if y_part < 70 {
-            y_part + 2000
-        } else {
-            y_part + 1900
+fn to_order(order: &str) -> Order {
+    match parse::to_order(order) {
+        Some(i) => i,
+        None => throw_r_error("order must be one of 'ymd', 'dmy' and 'mdy'"),
+    }
+}
+
+// parses every element of x, where the NA elements are None; the time part is
+// dropped unless the UTC offset (in seconds) is given
+fn parse_robj(x: &Robj, order: Order, pivot: i32, offset: Option<i32>) -> Vec<Option<ParseResult>> {
+    let to_date = |v: DateTimeResult| {
+        v.and_then(|(date, time)| match offset {
+            Some(offset) => parse::at_offset(date, time, offset),
+            None => Ok(date),
+        })
+    };
+    match x.rtype() {
+        // parses the levels once and maps the codes
+        Rtype::Integers if x.inherits("factor") => {
+            let levels = parse_robj(&x.get_attrib("levels").unwrap(), order, pivot, offset);
+            x.as_integer_slice()
+                .unwrap()
+                .iter()
+                .map(|i| {
+                    if i.is_na() {
+                        None
+                    } else {
+                        levels[*i as usize - 1]
+                    }
+                })
+                .collect()
+        }
+        Rtype::Doubles if x.inherits("integer64") => x
+            .as_real_slice()
+            .unwrap()
+            .iter()
+            .map(|i| i64_bits(*i).map(|v| to_date(parse::dbl2datetime(v as f64, order, pivot))))
+            .collect(),
+        Rtype::Logicals if x.as_logical_iter().unwrap().all(|i| i.is_na()) => vec![None; x.len()],
+        Rtype::Integers => x
+            .as_integer_vector()
+            .unwrap()
+            .iter()
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    Some(parse::int2date(*i, order, Some(pivot)))
+                }
+            })
+            .collect(),
+        Rtype::Doubles => x
+            .as_real_iter()
+            .unwrap()
+            .map(|i: &f64| {
+                if i.is_na() {
+                    None
+                } else {
+                    Some(to_date(parse::dbl2datetime(*i, order, pivot)))
+                }
+            })
+            .collect(),
+        Rtype::Strings => x
+            .as_str_vector()
+            .unwrap()
+            .iter()
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    Some(to_date(parse::str2datetime(i, order, pivot)))
+                }
+            })
+            .collect(),
+        _ => {
+            throw_r_error("x must be numeric or string vector");
         }
+    }
+}
+
+// the value of bit64's integer64, which stores the i64 in the bits of the
+// double; None for its NA
+fn i64_bits(x: f64) -> Option<i64> {
+    match x.to_bits() as i64 {
+        i64::MIN => None,
+        v => Some(v),
+    }
+}
+
+// the elements of x as they're written, e.g., 980308 or 1998-03-08, where the
+// NA elements are None
+fn robj2texts(x: &Robj) -> Vec<Option<String>> {
+    match x.rtype() {
+        Rtype::Integers if x.inherits("factor") => {
+            let levels = robj2texts(&x.get_attrib("levels").unwrap());
+            x.as_integer_slice()
+                .unwrap()
+                .iter()
+                .map(|i| {
+                    if i.is_na() {
+                        None
+                    } else {
+                        levels[*i as usize - 1].clone()
+                    }
+                })
+                .collect()
+        }
+        Rtype::Doubles if x.inherits("integer64") => x
+            .as_real_slice()
+            .unwrap()
+            .iter()
+            .map(|i| i64_bits(*i).map(|v| v.to_string()))
+            .collect(),
+        Rtype::Integers => x
+            .as_integer_slice()
+            .unwrap()
+            .iter()
+            .map(|i| if i.is_na() { None } else { Some(i.to_string()) })
+            .collect(),
+        Rtype::Doubles => x
+            .as_real_slice()
+            .unwrap()
+            .iter()
+            .map(|i| if i.is_na() { None } else { Some(i.to_string()) })
+            .collect(),
+        Rtype::Strings => x
+            .as_str_iter()
+            .unwrap()
+            .map(|i| if i.is_na() { None } else { Some(i.to_string()) })
+            .collect(),
+        _ => vec![None; x.len()],
+    }
+}
+
+// chooses the order that parses the most elements of a sample of x; on ties,
+// the one with more four-digit years, then the one with fewer leading two-digit
+// years (e.g., "01/02/03" is rarely 2001-02-03), then 'YMD', 'DMY' and 'MDY' in
+// turn. Returns the order and the most common format of the parsed elements.
+fn infer_order(x: &Robj, pivot: i32) -> (Order, Option<String>) {
+    const SAMPLE_SIZE: usize = 1000;
+    let sample = if x.len() > SAMPLE_SIZE {
+        // evenly spaced, one-based indices
+        let step = x.len() as f64 / SAMPLE_SIZE as f64;
+        let idx: Vec<i32> = (0..SAMPLE_SIZE)
+            .map(|i| (i as f64 * step) as i32 + 1)
+            .collect();
+        let mut sample = call!("[", x, idx).unwrap();
+        // the default method of `[` drops the class
+        if x.inherits("integer64") {
+            sample
+                .set_attrib("class", x.get_attrib("class").unwrap())
+                .unwrap();
+        }
+        sample
     } else {
-        y_part
+        x.clone()
     };
-    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+    let parsed: Vec<Vec<Option<ParseResult>>> = Order::ALL
+        .iter()
+        .map(|order| parse_robj(&sample, *order, pivot, None))
+        .collect();
+    let texts = robj2texts(&sample);
+    // the formats of the elements parsed in every order
+    let formats: Vec<Vec<Option<String>>> = Order::ALL
+        .iter()
+        .zip(&parsed)
+        .map(|(order, value)| {
+            value
+                .iter()
+                .zip(&texts)
+                .filter(|(v, _)| matches!(v, Some(Ok(_))))
+                .map(|(_, x)| parse::describe(x.as_deref()?, *order))
+                .collect()
+        })
+        .collect();
+    let score = |i: usize| {
+        let fmts = formats[i].iter().flatten();
+        (
+            formats[i].len(),
+            fmts.clone().filter(|f| f.contains("%Y")).count(),
+            fmts.filter(|f| !f.starts_with("%y")).count(),
+            std::cmp::Reverse(i),
+        )
+    };
+    let best = (0..Order::ALL.len()).max_by_key(|i| score(*i)).unwrap();
+    let order = Order::ALL[best];
+    let mut counts: Vec<(&String, usize)> = Vec::new();
+    for format in formats[best].iter().flatten() {
+        match counts.iter_mut().find(|(f, _)| *f == format) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((format, 1)),
+        }
+    }
+    // the first one on ties
+    let format = counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, n)| *n)
+        .map(|(f, _)| f.clone());
+    (order, format)
 }
 
-fn dbl2date(x: f64) -> Option<NaiveDate> {
-    if x % 1.0 == 0.0 {
-        int2date(x as i32, true)
-    } else {
+// tries the orders (e.g., "dmy") or the strptime-like formats (e.g.,
+// "%d.%m.%Y") in turn for every element and takes the first that succeeds;
+// returns the results with the matched order or format of every element
+fn parse_candidates<'a>(
+    x: &Robj,
+    candidates: &'a [String],
+    pivot: i32,
+    offset: Option<i32>,
+) -> (Vec<Option<ParseResult>>, Vec<Option<&'a str>>) {
+    let mut value: Vec<Option<ParseResult>> = vec![Some(Err(Reason::NoMatch)); x.len()];
+    let mut matched: Vec<Option<&str>> = vec![None; x.len()];
+    let mut texts: Option<Vec<Option<String>>> = None;
+    for candidate in candidates {
+        let res: Vec<Option<ParseResult>> = match parse::to_order(candidate) {
+            Some(order) => parse_robj(x, order, pivot, offset),
+            None => {
+                let items = match format::compile(candidate) {
+                    Some(i) => i,
+                    None => throw_r_error(format!("invalid format '{}'", candidate)),
+                };
+                texts
+                    .get_or_insert_with(|| robj2texts(x))
+                    .iter()
+                    .map(|i| {
+                        i.as_ref()
+                            .map(|i| format::strp2date(i, &items, pivot).ok_or(Reason::NoMatch))
+                    })
+                    .collect()
+            }
+        };
+        for (i, v) in res.into_iter().enumerate() {
+            match v {
+                None => value[i] = None,
+                Some(Ok(date)) if matched[i].is_none() && value[i].is_some() => {
+                    value[i] = Some(Ok(date));
+                    matched[i] = Some(candidate);
+                }
+                _ => {}
+            }
+        }
+        if matched
+            .iter()
+            .zip(&value)
+            .all(|(m, v)| m.is_some() || v.is_none())
+        {
+            break;
+        }
+    }
+    (value, matched)
+}
+
+// throws an R error with the number of the failures and the first few of them
+fn check_failures(x: &Robj, value: &[Option<ParseResult>]) {
+    const MAX_SHOWN: usize = 5;
+    let failures: Vec<(usize, Reason)> = value
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| match v {
+            Some(Err(reason)) => Some((i, *reason)),
+            _ => None,
+        })
+        .collect();
+    if failures.is_empty() {
+        return;
+    }
+    let texts = robj2texts(x);
+    let quoted = x.rtype() == Rtype::Strings || x.inherits("factor");
+    let mut msg = format!("failed to parse {} element(s) of x:", failures.len());
+    for (i, reason) in failures.iter().take(MAX_SHOWN) {
+        msg.push_str(&format!(
+            "\n  x[{}]: {} ({})",
+            i + 1,
+            match (&texts[*i], quoted) {
+                (Some(text), true) => format!("\"{}\"", text),
+                (Some(text), false) => text.clone(),
+                (None, _) => "NA".to_string(),
+            },
+            reason.message()
+        ));
+    }
+    if failures.len() > MAX_SHOWN {
+        msg.push_str(&format!("\n  ... and {} more", failures.len() - MAX_SHOWN));
+    }
+    throw_r_error(msg);
+}
+
+#[extendr]
+fn rust_ymd(
+    x: Robj,
+    order: Vec<String>,
+    pivot: i32,
+    strict: bool,
+    utc_offset: f64,
+    tz: &str,
+) -> Robj {
+    if x.inherits("Date") {
+        return rdate::date_as_is(x);
+    }
+    if rdate::is_posix(&x) {
+        return rdate::dates_like(&rdate::posix2date(&x, tz).unwrap(), &x);
+    }
+    let offset = if utc_offset.is_na() {
         None
+    } else {
+        Some(utc_offset.round() as i32)
+    };
+    // "auto" infers the order and reports it with the format as attributes;
+    // several orders or any formats are tried in turn, reporting the matched one
+    let mut attrs: Vec<(&str, Robj)> = Vec::new();
+    let value = match &order[..] {
+        [o] if o == "auto" => {
+            let (order, format) = infer_order(&x, pivot);
+            attrs.push(("order", r!(order.name())));
+            attrs.push(("format", std::iter::once(format.as_deref()).collect_robj()));
+            parse_robj(&x, order, pivot, offset)
+        }
+        [o] if parse::to_order(o).is_some() => parse_robj(&x, to_order(o), pivot, offset),
+        _ => {
+            let (value, matched) = parse_candidates(&x, &order, pivot, offset);
+            attrs.push(("matched", matched.into_iter().collect_robj()));
+            value
+        }
+    };
+    if strict {
+        check_failures(&x, &value);
     }
+    let value: Vec<Option<NaiveDate>> = value.into_iter().map(|v| v.and_then(|v| v.ok())).collect();
+    let mut out = rdate::dates_like(&value, &x);
+    for (name, attr) in attrs {
+        out.set_attrib(name, attr).unwrap();
+    }
+    out
 }
 
-fn str2date(x: &str) -> Option<NaiveDate> {
-    match x.parse::<i32>() {
-        Ok(v) => int2date(v, true),
-        Err(_) => {
-            let v: Vec<&str> = x.split(&['-', '.', '/', ' '][..]).collect();
-            if v.len() == 3 {
-                let short_year = v[0].len() <= 2;
-                let year: i32 = v[0].parse().ok()?;
-                let month: i32 = v[1].parse().ok()?;
-                let day: i32 = v[2].parse().ok()?;
-                int2date(year * 10000 + month * 100 + day, short_year)
-            } else {
-                None
+#[extendr]
+fn rust_ymd_diagnose(x: Robj, order: &str, pivot: i32) -> Robj {
+    let value = if x.inherits("Date") || rdate::is_posix(&x) {
+        rdate::robj2date(x, "x")
+            .unwrap()
+            .into_iter()
+            .map(|v| v.map(Ok))
+            .collect()
+    } else {
+        parse_robj(&x, to_order(order), pivot, None)
+    };
+    let date: Vec<Option<NaiveDate>> = value.iter().map(|v| v.and_then(|v| v.ok())).collect();
+    let reason: Vec<Option<Reason>> = value.iter().map(|v| v.and_then(|v| v.err())).collect();
+    list!(
+        date = rdate::to_rdate_as(&date, rdate::integer_option()),
+        code = reason.iter().map(|v| v.map(|v| v.code())).collect_robj(),
+        message = reason.iter().map(|v| v.map(|v| v.message())).collect_robj()
+    )
+    .into()
+}
+
+// whether every element parses in the order with the year in [min_year,
+// max_year], without creating the Date; NA for the NA elements
+#[extendr]
+fn rust_is_ymd(x: Robj, order: &str, pivot: i32, min_year: i32, max_year: i32) -> Robj {
+    let value: Vec<Option<bool>> = if x.inherits("Date") || rdate::is_posix(&x) {
+        rdate::robj2date(x, "x")
+            .unwrap()
+            .into_iter()
+            .map(|v| v.map(|date| (min_year..=max_year).contains(&date.year())))
+            .collect()
+    } else {
+        parse_robj(&x, to_order(order), pivot, None)
+            .into_iter()
+            .map(|v| v.map(|v| v.is_ok_and(|date| (min_year..=max_year).contains(&date.year()))))
+            .collect()
+    };
+    value
+        .into_iter()
+        .map(|v| v.map_or(Rbool::na(), Rbool::from))
+        .collect::<Logicals>()
+        .into()
+}
+
+// every distinct date that the element parses into in the orders of 'YMD', 'DMY'
+// and 'MDY', with the orders (joined by ",") that give it; the two-digit years
+// are read by the pivot (the short year) and then as they are, e.g., 98 as 0098
+// (the long year, labelled like "ymd_long"), which is only listed when it's
+// a different date
+#[extendr]
+fn rust_ymd_candidates(x: Robj, pivot: i32) -> Robj {
+    let value: Vec<Vec<Option<ParseResult>>> = if x.inherits("Date") || rdate::is_posix(&x) {
+        let date: Vec<Option<ParseResult>> = rdate::robj2date(x, "x")
+            .unwrap()
+            .into_iter()
+            .map(|v| v.map(Ok))
+            .collect();
+        vec![date]
+    } else {
+        let short: Vec<Vec<Option<ParseResult>>> = Order::ALL
+            .iter()
+            .map(|order| parse_robj(&x, *order, pivot, None))
+            .collect();
+        // the pivot 0 keeps the two-digit years as they are
+        let long: Vec<Vec<Option<ParseResult>>> = Order::ALL
+            .iter()
+            .zip(&short)
+            .map(|(order, short)| {
+                parse_robj(&x, *order, 0, None)
+                    .into_iter()
+                    .zip(short)
+                    .map(|(v, s)| v.filter(|v| v != s.as_ref().unwrap()))
+                    .collect()
+            })
+            .collect();
+        short.into_iter().chain(long).collect()
+    };
+    // Date input has no order
+    let names: Vec<Option<String>> = if value.len() == 1 {
+        vec![None]
+    } else {
+        let short = Order::ALL
+            .iter()
+            .map(|order| Some(order.name().to_string()));
+        let long = Order::ALL
+            .iter()
+            .map(|order| Some(format!("{}_long", order.name())));
+        short.chain(long).collect()
+    };
+    let integer = rdate::integer_option();
+    let mut n: Vec<Option<i32>> = Vec::with_capacity(value[0].len());
+    let mut candidates: Vec<Robj> = Vec::with_capacity(value[0].len());
+    let mut orders: Vec<Robj> = Vec::with_capacity(value[0].len());
+    for i in 0..value[0].len() {
+        let mut found: Vec<(NaiveDate, Vec<&str>)> = Vec::new();
+        // the long-year readings are listed but not counted in `n`
+        let mut count = 0;
+        for (j, (v, name)) in value.iter().zip(&names).enumerate() {
+            if let Some(Ok(date)) = v[i] {
+                match found.iter_mut().find(|(d, _)| *d == date) {
+                    Some((_, o)) => o.extend(name.as_deref()),
+                    None => {
+                        found.push((date, name.as_deref().into_iter().collect()));
+                        if j < Order::ALL.len() {
+                            count += 1;
+                        }
+                    }
+                }
             }
         }
+        let dates: Vec<Option<NaiveDate>> = found.iter().map(|(d, _)| Some(*d)).collect();
+        n.push(if value[0][i].is_none() {
+            None
+        } else {
+            Some(count)
+        });
+        candidates.push(rdate::to_rdate_as(&dates, integer));
+        orders.push(
+            found
+                .iter()
+                .map(|(_, o)| Some(o.join(",")).filter(|_| !o.is_empty()))
+                .collect_robj(),
+        );
     }
+    list!(
+        n = n.into_iter().collect_robj(),
+        candidates = List::from_values(candidates),
+        orders = List::from_values(orders)
+    )
+    .into()
 }
 
 #[extendr]
-fn rust_ymd(x: Robj) -> Robj {
+fn rust_strpdate(x: Robj, format: &str, pivot: i32) -> Robj {
     if x.inherits("Date") {
-        return x;
+        return rdate::date_as_is(x);
     }
+    let items = match format::compile(format) {
+        Some(i) => i,
+        None => throw_r_error(format!("invalid format '{}'", format)),
+    };
+    let value: Vec<Option<NaiveDate>> = match x.rtype() {
+        Rtype::Strings => x
+            .as_str_vector()
+            .unwrap()
+            .iter()
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    format::strp2date(i, &items, pivot)
+                }
+            })
+            .collect(),
+        _ => {
+            throw_r_error("x must be a string vector");
+        }
+    };
+    rdate::dates_like(&value, &x)
+}
+
+#[extendr]
+fn rust_strfdate(x: Robj, format: &str) -> Robj {
+    let items = match format::compile(format) {
+        Some(i) => i,
+        None => throw_r_error(format!("invalid format '{}'", format)),
+    };
+    robj2ymd(x, "x")
+        .iter()
+        .map(|v| v.as_ref().and_then(|v| format::date2strf(v, &items)))
+        .collect::<Vec<Option<String>>>()
+        .iter()
+        .map(|v| v.as_deref())
+        .collect_robj()
+}
+
+#[extendr]
+fn rust_date2int(x: Robj, format: &str) -> Robj {
+    let layout = match parse::to_compact(format) {
+        Some(v) => v,
+        None => throw_r_error("format must be one of 'yyyymmdd', 'yymmdd' and 'yyyymm'"),
+    };
+    let out: Vec<Option<i32>> = robj2ymd(x, "x")
+        .iter()
+        .map(|v| v.as_ref().and_then(|v| parse::date2int(v, layout)))
+        .collect();
+    r!(out)
+}
+
+#[extendr]
+fn rust_label(x: Robj, anchor: &str) -> Robj {
+    let fun = match anchor {
+        "begin" => period::bop,
+        "end" => period::eop,
+        _ => throw_r_error("anchor must be 'begin' or 'end'"),
+    };
+    let label = |x: &str| parse::label2period(x).map(|(date, p)| fun(&date, p));
     let value: Vec<Option<NaiveDate>> = match x.rtype() {
         Rtype::Integers => x
             .as_integer_vector()
             .unwrap()
             .iter()
-            .map(|i| if i.is_na() { None } else { int2date(*i, true) })
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    label(&i.to_string())
+                }
+            })
             .collect(),
         Rtype::Doubles => x
             .as_real_iter()
             .unwrap()
-            .map(|i: &f64| if i.is_na() { None } else { dbl2date(*i) })
+            .map(|i: &f64| {
+                if i.is_na() || i % 1.0 != 0.0 {
+                    None
+                } else {
+                    label(&(*i as i64).to_string())
+                }
+            })
             .collect(),
         Rtype::Strings => x
             .as_str_vector()
             .unwrap()
             .iter()
-            .map(|i| if i.is_na() { None } else { str2date(i) })
+            .map(|i| if i.is_na() { None } else { label(i) })
             .collect(),
         _ => {
             throw_r_error("x must be numeric or string vector");
         }
     };
-    value.to_rdate()
+    rdate::dates_like(&value, &x)
+}
+
+// the first (or all) dates found in every string, with the one-based positions
+// of the first and the last characters of the tokens; when only the first is
+// wanted, every element has a row, which is NA if no date is found
+#[extendr]
+fn rust_extract(x: Robj, order: &str, pivot: i32, all: bool) -> Robj {
+    if x.rtype() != Rtype::Strings {
+        throw_r_error("x must be a string vector");
+    }
+    let order = to_order(order);
+    let mut index: Vec<i32> = Vec::new();
+    let mut date: Vec<Option<NaiveDate>> = Vec::new();
+    let mut start: Vec<Option<i32>> = Vec::new();
+    let mut end: Vec<Option<i32>> = Vec::new();
+    for (i, s) in x.as_str_iter().unwrap().enumerate() {
+        let mut found = if s.is_na() {
+            Vec::new()
+        } else {
+            parse::scan_dates(s, order, pivot)
+        };
+        if !all {
+            found.truncate(1);
+            if found.is_empty() {
+                index.push(i as i32 + 1);
+                date.push(None);
+                start.push(None);
+                end.push(None);
+            }
+        }
+        for (d, b, e) in found {
+            index.push(i as i32 + 1);
+            date.push(Some(d));
+            start.push(Some(b as i32 + 1));
+            end.push(Some(e as i32 + 1));
+        }
+    }
+    list!(
+        index = index,
+        date = rdate::to_rdate_as(&date, rdate::integer_option()),
+        start = start.into_iter().collect_robj(),
+        end = end.into_iter().collect_robj()
+    )
+    .into()
+}
+
+// imputes the partial dates, "2021" (year) and "2021-07" or "202107" (month), to
+// the first, last or middle day of the period, where the middle is the 15th of
+// the month or July 1 of the year. The full dates are parsed like `ymd()`, but
+// six digits are YYYYMM unless yyyymm is false, which tries YYMMDD first. The
+// "imputed" attribute is "M" if the month and the day are imputed, "D" if only
+// the day is, and NA otherwise.
+#[extendr]
+fn rust_impute(x: Robj, rule: &str, pivot: i32, yyyymm: bool) -> Robj {
+    let fun: fn(&NaiveDate, period::Period) -> NaiveDate = match rule {
+        "first" => period::bop,
+        "last" => period::eop,
+        "middle" => |x, p| match p {
+            period::Period::Year => period::add_months(&period::bop(x, p), 6),
+            _ => period::add_days(&period::bop(x, p), 14),
+        },
+        _ => throw_r_error("rule must be one of 'first', 'last' and 'middle'"),
+    };
+    let (value, imputed): (Vec<Option<NaiveDate>>, Vec<Option<&str>>) =
+        if x.inherits("Date") || rdate::is_posix(&x) {
+            let value = rdate::robj2date(x.clone(), "x").unwrap();
+            let n = value.len();
+            (value, vec![None; n])
+        } else {
+            if !matches!(
+                x.rtype(),
+                Rtype::Integers | Rtype::Doubles | Rtype::Strings | Rtype::Logicals
+            ) {
+                throw_r_error("x must be numeric or string vector");
+            }
+            robj2texts(&x)
+                .iter()
+                .map(|i| {
+                    let Some(i) = i else {
+                        return (None, None);
+                    };
+                    let full = || {
+                        parse::str2datetime(i, Order::Ymd, pivot)
+                            .ok()
+                            .map(|(date, _)| date)
+                    };
+                    if !yyyymm {
+                        if let Some(date) = full() {
+                            return (Some(date), None);
+                        }
+                    }
+                    match parse::partial2period(i) {
+                        Some((date, p)) => {
+                            let flag = if p == period::Period::Year { "M" } else { "D" };
+                            (Some(fun(&date, p)), Some(flag))
+                        }
+                        None if yyyymm => (full(), None),
+                        None => (None, None),
+                    }
+                })
+                .unzip()
+        };
+    let mut out = rdate::dates_like(&value, &x);
+    out.set_attrib("imputed", imputed.into_iter().collect_robj())
+        .unwrap();
+    out
+}
+
+// converts the input to Date via the same rules as `ymd()` with the default pivot
+fn robj2ymd(x: Robj, var: &str) -> Vec<Option<NaiveDate>> {
+    rdate::robj2date(
+        rust_ymd(
+            x,
+            vec!["ymd".to_string()],
+            parse::DEFAULT_PIVOT,
+            false,
+            f64::na(),
+            "",
+        ),
+        var,
+    )
+    .unwrap()
 }
 
 fn beop(x: Robj, unit: &str, fun: fn(&NaiveDate, period::Period) -> NaiveDate) -> Robj {
     let p = match period::to_period(unit) {
         Some(i) => i,
-        None => return (vec![None; x.len()]).to_rdate(),
+        None => return rdate::dates_like(&vec![None; x.len()], &x),
     };
-    let x = rdate::robj2date(rust_ymd(x), "x").unwrap();
-    let out: Vec<Option<NaiveDate>> = x
+    let out: Vec<Option<NaiveDate>> = robj2ymd(x.clone(), "x")
         .iter()
         .map(|v| v.as_ref().map(|date| fun(date, p)))
         .collect();
-    out.to_rdate()
+    rdate::dates_like(&out, &x)
 }
 
 #[extendr]
@@ -119,12 +738,227 @@ fn period_end(x: Robj, unit: &str) -> Robj {
 /// @export
 #[extendr]
 fn edate(ref_date: Robj, months: i32) -> Robj {
-    let out: Vec<Option<NaiveDate>> = rdate::robj2date(rust_ymd(ref_date), "ref_date")
-        .unwrap()
+    let out: Vec<Option<NaiveDate>> = robj2ymd(ref_date.clone(), "ref_date")
         .iter()
         .map(|v| v.as_ref().map(|date| period::add_months(date, months)))
         .collect();
-    out.to_rdate()
+    rdate::dates_like(&out, &ref_date)
+}
+
+/// Convert between Excel serial numbers and Date
+/// @param x a numeric vector of Excel serial numbers. The fractional part,
+///   i.e., the time of the day, is dropped.
+/// @param date1904 whether the workbook uses the 1904 date system, where the
+///   serial 0 is 1904-01-01. Otherwise, the 1900 date system is used, where
+///   the serial 1 is 1900-01-01 and the serial 60 is the non-existent
+///   1900-02-29, which is inherited from Lotus 1-2-3.
+/// @return `excel2date()` returns a Date vector and `date2excel()` returns an
+///   integer vector. The values out of the range that Excel supports
+///   (from 1900-01-01 or 1904-01-01 to 9999-12-31) return `NA`.
+/// @examples
+/// excel2date(c(44555, 44555.5, 60, 61))
+/// excel2date(43093, date1904 = TRUE)
+/// date2excel(as.Date("2021-12-25"))
+/// ## supports 'YMD' formatted integer or string
+/// date2excel(c(211225, 19000301))
+/// @name excel
+/// @export
+#[extendr]
+fn excel2date(x: Robj, #[extendr(default = "FALSE")] date1904: bool) -> Robj {
+    let value: Vec<Option<NaiveDate>> = match x.rtype() {
+        Rtype::Integers => x
+            .as_integer_vector()
+            .unwrap()
+            .iter()
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    excel::serial2date(*i as f64, date1904)
+                }
+            })
+            .collect(),
+        Rtype::Doubles => x
+            .as_real_iter()
+            .unwrap()
+            .map(|i: &f64| excel::serial2date(*i, date1904))
+            .collect(),
+        _ => {
+            throw_r_error("x must be a numeric vector");
+        }
+    };
+    rdate::dates_like(&value, &x)
+}
+
+/// @rdname excel
+/// @param ref_date a Date vector. It will try to convert the input to date via
+///   [ymd()], if the input is not a Date.
+/// @export
+#[extendr]
+fn date2excel(ref_date: Robj, #[extendr(default = "FALSE")] date1904: bool) -> Robj {
+    let out: Vec<Option<i32>> = robj2ymd(ref_date, "ref_date")
+        .iter()
+        .map(|v| {
+            v.as_ref()
+                .and_then(|date| excel::date2serial(date, date1904))
+        })
+        .collect();
+    r!(out)
+}
+
+fn to_epoch(system: &str) -> rdate::Epoch {
+    match rdate::to_epoch(system) {
+        Some(v) => v,
+        None => throw_r_error(
+            "system must be one of 'sas', 'stata_td', 'stata_tm', 'stata_tq', 'spss' and 'matlab'",
+        ),
+    }
+}
+
+/// Convert between the dates of statistical software and Date
+/// @param x a numeric vector of the dates in the statistical software. The
+///   fractional part, e.g., the time of the day, is dropped.
+/// @param system the epoch and the unit of the dates. `"sas"` and `"stata_td"`
+///   are the days since 1960-01-01, `"stata_tm"` and `"stata_tq"` are the
+///   months and quarters since 1960-01, `"spss"` is the seconds since
+///   1582-10-14 and `"matlab"` is MATLAB's `datenum`, where 1 is 0000-01-01.
+/// @return `epoch2date()` returns a Date vector, where the months and quarters
+///   become their first days and the values out of the range of Date return
+///   `NA`. `date2epoch()` returns a numeric vector, where the dates become the
+///   months or quarters they fall in for `"stata_tm"` and `"stata_tq"`.
+/// @examples
+/// epoch2date(22639, "sas")
+/// epoch2date(c(743, 744), "stata_tm")
+/// epoch2date(247, "stata_tq")
+/// epoch2date(13859769600, "spss")
+/// epoch2date(738515.5, "matlab")
+/// date2epoch(as.Date("2021-12-25"), "stata_tq")
+/// ## supports 'YMD' formatted integer or string
+/// date2epoch(211225, "spss")
+/// @name epoch
+/// @export
+#[extendr]
+fn epoch2date(x: Robj, system: &str) -> Robj {
+    let epoch = to_epoch(system);
+    let value: Vec<Option<NaiveDate>> = match x.rtype() {
+        Rtype::Integers => x
+            .as_integer_vector()
+            .unwrap()
+            .iter()
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    rdate::epoch2date(*i as f64, epoch)
+                }
+            })
+            .collect(),
+        Rtype::Doubles => x
+            .as_real_iter()
+            .unwrap()
+            .map(|i: &f64| rdate::epoch2date(*i, epoch))
+            .collect(),
+        _ => {
+            throw_r_error("x must be a numeric vector");
+        }
+    };
+    rdate::dates_like(&value, &x)
+}
+
+/// @rdname epoch
+/// @param ref_date a Date vector. It will try to convert the input to date via
+///   [ymd()], if the input is not a Date.
+/// @export
+#[extendr]
+fn date2epoch(ref_date: Robj, system: &str) -> Robj {
+    let epoch = to_epoch(system);
+    let out: Vec<Option<f64>> = robj2ymd(ref_date, "ref_date")
+        .iter()
+        .map(|v| v.as_ref().map(|date| rdate::date2epoch(date, epoch)))
+        .collect();
+    r!(out)
+}
+
+// the seconds of Unix timestamps in the unit, e.g., 1000 for milliseconds,
+// where the integer64 and integer strings are divided exactly
+fn unix_secs(x: &Robj, unit: i64) -> Vec<Option<f64>> {
+    let from_i64 = |v: i64| v.div_euclid(unit) as f64;
+    let from_f64 = |v: f64| {
+        if v.is_finite() {
+            Some((v / unit as f64).floor())
+        } else {
+            None
+        }
+    };
+    match x.rtype() {
+        Rtype::Doubles if x.inherits("integer64") => x
+            .as_real_slice()
+            .unwrap()
+            .iter()
+            .map(|i| i64_bits(*i).map(from_i64))
+            .collect(),
+        Rtype::Logicals if x.as_logical_iter().unwrap().all(|i| i.is_na()) => vec![None; x.len()],
+        Rtype::Integers => x
+            .as_integer_slice()
+            .unwrap()
+            .iter()
+            .map(|i| {
+                if i.is_na() {
+                    None
+                } else {
+                    Some(from_i64(*i as i64))
+                }
+            })
+            .collect(),
+        Rtype::Doubles => x.as_real_iter().unwrap().map(|i| from_f64(*i)).collect(),
+        Rtype::Strings => x
+            .as_str_iter()
+            .unwrap()
+            .map(|i| {
+                if i.is_na() {
+                    return None;
+                }
+                let i = i.trim();
+                match i.parse::<i64>() {
+                    Ok(v) => Some(from_i64(v)),
+                    Err(_) => i.parse::<f64>().ok().and_then(from_f64),
+                }
+            })
+            .collect(),
+        _ => {
+            throw_r_error("x must be numeric or string vector");
+        }
+    }
+}
+
+#[extendr]
+fn rust_unix2date(x: Robj, unit: &str, utc_offset: f64, tz: &str) -> Robj {
+    let unit = match rdate::to_unit(unit) {
+        Some(v) => v,
+        None => throw_r_error("unit must be one of 's', 'ms', 'us' and 'ns'"),
+    };
+    let offset = if utc_offset.is_na() {
+        0
+    } else {
+        utc_offset.round() as i32
+    };
+    // the timestamps out of the range of Date are NA before R sees them
+    let value: Vec<Option<NaiveDate>> = if tz.is_empty() {
+        unix_secs(&x, unit)
+            .into_iter()
+            .map(|v| v.and_then(|v| rdate::unix2date(v, offset)))
+            .collect()
+    } else {
+        let secs: Vec<Option<f64>> = unix_secs(&x, unit)
+            .into_iter()
+            .map(|v| v.filter(|v| rdate::unix2date(*v, 0).is_some()))
+            .collect();
+        let mut ct = r!(secs);
+        ct.set_class(&["POSIXct", "POSIXt"]).unwrap();
+        ct.set_attrib("tzone", "UTC").unwrap();
+        rdate::posix2date(&ct, tz).unwrap()
+    };
+    rdate::dates_like(&value, &x)
 }
 
 macro_rules! make_date_part_fun {
@@ -133,7 +967,7 @@ macro_rules! make_date_part_fun {
         /// @export
         #[extendr]
         fn $fn_name(ref_date: Robj) -> Robj {
-            let ref_date = rdate::robj2date(rust_ymd(ref_date), "ref_date").unwrap();
+            let ref_date = robj2ymd(ref_date, "ref_date");
             r!($method(&ref_date))
         }
     };
@@ -145,6 +979,7 @@ make_date_part_fun!(quarter, dateof::quarter);
 make_date_part_fun!(wday, dateof::wday);
 make_date_part_fun!(mday, dateof::mday);
 make_date_part_fun!(yday, dateof::yday);
+make_date_part_fun!(isoyear, dateof::isoyear);
 make_date_part_fun!(isoweek, dateof::isoweek);
 make_date_part_fun!(isowday, dateof::isowday);
 
@@ -153,126 +988,6 @@ mod test {
     use super::*;
     use chrono::NaiveDate;
     #[test]
-    fn integers() {
-        assert_eq!(
-            int2date(980308, true).unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            int2date(980308, false).unwrap(),
-            NaiveDate::from_ymd_opt(98, 3, 8).unwrap()
-        );
-        assert_eq!(
-            int2date(50308, true).unwrap(),
-            NaiveDate::from_ymd_opt(2005, 3, 8).unwrap()
-        );
-        assert_eq!(
-            int2date(19980308, true).unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            int2date(21050308, true).unwrap(),
-            NaiveDate::from_ymd_opt(2105, 3, 8).unwrap()
-        );
-        assert_eq!(int2date(980230, true), None);
-        assert_eq!(int2date(19980230, true), None);
-        assert_eq!(int2date(22, true), None);
-        assert_eq!(
-            int2date(2201010, true).unwrap(),
-            NaiveDate::from_ymd_opt(220, 10, 10).unwrap()
-        );
-    }
-
-    #[test]
-    fn doubles() {
-        assert_eq!(
-            dbl2date(980308.).unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            dbl2date(050308.).unwrap(),
-            NaiveDate::from_ymd_opt(2005, 3, 8).unwrap()
-        );
-        assert_eq!(
-            dbl2date(19980308.).unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            dbl2date(21050308.).unwrap(),
-            NaiveDate::from_ymd_opt(2105, 3, 8).unwrap()
-        );
-        assert_eq!(dbl2date(980230.), None);
-        assert_eq!(dbl2date(19980230.), None);
-        assert_eq!(dbl2date(980230.1), None);
-        assert_eq!(dbl2date(2201310.), None);
-        assert_eq!(dbl2date(220101.5), None);
-    }
-    #[test]
-    fn strings() {
-        assert_eq!(
-            str2date("980308").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("98.3.08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("98.3.8").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("98.03.08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("98/03/08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("98-03-08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-
-        assert_eq!(
-            str2date("220102").unwrap(),
-            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
-        );
-        assert_eq!(
-            str2date("22.01.02").unwrap(),
-            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
-        );
-        assert_eq!(
-            str2date("22/01/02").unwrap(),
-            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
-        );
-        assert_eq!(
-            str2date("22-01-02").unwrap(),
-            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
-        );
-
-        assert_eq!(
-            str2date("19980308").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("1998.03.08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("1998/03/08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-        assert_eq!(
-            str2date("1998-03-08").unwrap(),
-            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
-        );
-
-        assert_eq!(str2date("98308"), None);
-        assert_eq!(str2date("980338"), None);
-        assert_eq!(str2date("9a0308"), None);
-    }
-    #[test]
     fn to_date() {
         test! {
             let x: Robj = r!([18990.0, 18991.0]).set_class(&["Date"]).unwrap().clone();
@@ -287,12 +1002,27 @@ mod test {
 extendr_module! {
     mod ymd;
     fn rust_ymd;
+    fn rust_ymd_diagnose;
+    fn rust_ymd_candidates;
+    fn rust_is_ymd;
+    fn rust_strpdate;
+    fn rust_strfdate;
+    fn rust_date2int;
+    fn rust_label;
+    fn rust_impute;
+    fn rust_extract;
+    fn rust_unix2date;
     fn period_begin;
     fn period_end;
     fn edate;
+    fn excel2date;
+    fn date2excel;
+    fn epoch2date;
+    fn date2epoch;
     fn year;
     fn month;
     fn quarter;
+    fn isoyear;
     fn isoweek;
     fn isowday;
     fn wday;
@@ -0,0 +1,1486 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::dateof;
+use crate::period::Period;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Order {
+    Ymd,
+    Dmy,
+    Mdy,
+}
+
+pub fn to_order(x: &str) -> Option<Order> {
+    match x {
+        "ymd" => Some(Order::Ymd),
+        "dmy" => Some(Order::Dmy),
+        "mdy" => Some(Order::Mdy),
+        _ => None,
+    }
+}
+
+impl Order {
+    pub const ALL: [Order; 3] = [Order::Ymd, Order::Dmy, Order::Mdy];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Order::Ymd => "ymd",
+            Order::Dmy => "dmy",
+            Order::Mdy => "mdy",
+        }
+    }
+}
+
+// why an element fails to be parsed
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Reason {
+    NonInteger,
+    OutOfRange,
+    BadSeparator,
+    BadFieldCount,
+    BadToken,
+    InvalidDate,
+    BadTime,
+    NoMatch,
+}
+
+impl Reason {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Reason::NonInteger => "non_integer",
+            Reason::OutOfRange => "out_of_range",
+            Reason::BadSeparator => "bad_separator",
+            Reason::BadFieldCount => "bad_field_count",
+            Reason::BadToken => "bad_token",
+            Reason::InvalidDate => "invalid_date",
+            Reason::BadTime => "bad_time",
+            Reason::NoMatch => "no_match",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Reason::NonInteger => "the number has a decimal part",
+            Reason::OutOfRange => "the number is out of the supported range",
+            Reason::BadSeparator => "contains unsupported separators or characters",
+            Reason::BadFieldCount => "the number of the date fields is not three",
+            Reason::BadToken => "a field is neither a number nor a month name",
+            Reason::InvalidDate => "the date doesn't exist, e.g., the day is out of the month",
+            Reason::BadTime => "the time part is neither a valid time nor a UTC offset",
+            Reason::NoMatch => "matches none of the orders or formats",
+        }
+    }
+}
+
+pub type ParseResult = Result<NaiveDate, Reason>;
+
+// the time of the day in seconds, and the UTC offset in seconds if the timestamp
+// carries one, e.g., "Z" or "+08:00"
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Time {
+    pub secs: i32,
+    pub zone: Option<i32>,
+}
+
+pub type DateTimeResult = Result<(NaiveDate, Option<Time>), Reason>;
+
+// The first year of the 100-year window that two-digit years are mapped into,
+// e.g., 70 => 1970, 99 => 1999 and 69 => 2069
+pub const DEFAULT_PIVOT: i32 = 1970;
+
+// maps the two-digit year to the year in [pivot, pivot + 99] with the same last
+// two digits; None if it overflows, e.g., for the pivot of i32::MAX
+pub fn expand_year(y: i32, pivot: i32) -> Option<i32> {
+    let year = pivot as i64 + (y as i64 - pivot as i64).rem_euclid(100);
+    i32::try_from(year).ok()
+}
+
+// pivot: when it's Some, the year 98 will be converted to 1998 (with the window
+// starting from 1970); when it's None, it will be regarded as 0098
+fn fields2date(year: i32, month: i32, day: i32, pivot: Option<i32>) -> ParseResult {
+    let year = match pivot {
+        // 70 => 1970; 98 => 1998; 69 => 2069; 1 => 2001
+        Some(pivot) if (0..100).contains(&year) => {
+            expand_year(year, pivot).ok_or(Reason::OutOfRange)?
+        }
+        _ => year,
+    };
+    let (month, day) = match (month.try_into(), day.try_into()) {
+        (Ok(month), Ok(day)) => (month, day),
+        _ => return Err(Reason::InvalidDate),
+    };
+    NaiveDate::from_ymd_opt(year, month, day).ok_or(Reason::InvalidDate)
+}
+
+// the field must be a number
+fn field(x: &str) -> Result<i32, Reason> {
+    x.parse().map_err(|_| Reason::BadToken)
+}
+
+// Ymd: 980102 and 19980102
+// Dmy: 020198 and 02011998 (the leading zero may be dropped for integers)
+// Mdy: 010298 and 01021998
+pub fn int2date(x: i32, order: Order, pivot: Option<i32>) -> ParseResult {
+    if order == Order::Ymd {
+        return fields2date(x / 10000, x / 100 % 100, x % 100, pivot);
+    }
+    // 7 or 8 digits mean the year at the end has four digits
+    let (first, second, year, pivot) = if x >= 1_000_000 {
+        (x / 1_000_000, x / 10000 % 100, x % 10000, None)
+    } else {
+        (x / 10000, x / 100 % 100, x % 100, pivot)
+    };
+    match order {
+        Order::Dmy => fields2date(year, second, first, pivot),
+        _ => fields2date(year, first, second, pivot),
+    }
+}
+
+// the layouts of the compact integer dates
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Compact {
+    Yyyymmdd,
+    Yymmdd,
+    Yyyymm,
+}
+
+pub fn to_compact(x: &str) -> Option<Compact> {
+    match x {
+        "yyyymmdd" => Some(Compact::Yyyymmdd),
+        "yymmdd" => Some(Compact::Yymmdd),
+        "yyyymm" => Some(Compact::Yyyymm),
+        _ => None,
+    }
+}
+
+// the inverse of int2date() in the 'YMD' order, e.g., 2021-12-25 => 20211225,
+// 211225 or 202112; None for the years out of 0-9999
+pub fn date2int(x: &NaiveDate, layout: Compact) -> Option<i32> {
+    if !(0..=9999).contains(&x.year()) {
+        return None;
+    }
+    let (y, m, d) = (x.year(), x.month() as i32, x.day() as i32);
+    Some(match layout {
+        Compact::Yyyymmdd => y * 10000 + m * 100 + d,
+        Compact::Yymmdd => y % 100 * 10000 + m * 100 + d,
+        Compact::Yyyymm => y * 100 + m,
+    })
+}
+
+pub fn dbl2date(x: f64, order: Order, pivot: i32) -> ParseResult {
+    if !x.is_finite() || x.abs() > i32::MAX as f64 {
+        Err(Reason::OutOfRange)
+    } else if x % 1.0 != 0.0 {
+        Err(Reason::NonInteger)
+    } else {
+        int2date(x as i32, order, Some(pivot))
+    }
+}
+
+// the same as dbl2date() but also accepts the 14-digit compact timestamps like
+// 20211225134500, which exceed the range of i32
+pub fn dbl2datetime(x: f64, order: Order, pivot: i32) -> DateTimeResult {
+    if x.is_finite() && x % 1.0 == 0.0 && (1e13..1e14).contains(&x.abs()) {
+        let time = (x.abs() % 1e6) as i32;
+        let time = hms2time(time / 10000, time / 100 % 100, time % 100, None);
+        let date = dbl2date((x / 1e6).trunc(), order, pivot)?;
+        end_of_day(date, Some(time.ok_or(Reason::BadTime)?))
+    } else {
+        Ok((dbl2date(x, order, pivot)?, None))
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+// the full English month name or its three-letter abbreviation, case-insensitive
+fn month_name(x: &str) -> Option<i32> {
+    let x = x.to_ascii_lowercase();
+    if x == "sept" {
+        return Some(9);
+    }
+    MONTH_NAMES
+        .iter()
+        .position(|name| x == *name || (x.len() == 3 && name.starts_with(&x)))
+        .map(|i| i as i32 + 1)
+}
+
+// splits into runs of digits and runs of letters, e.g., "25DEC21" and
+// "Dec 25, 2021"; any other characters except the separators lead to None
+fn tokenize(x: &str) -> Option<Vec<&str>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut chars = x.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !c.is_ascii_alphanumeric() {
+            if !['-', '.', '/', ' ', ','].contains(&c) {
+                return None;
+            }
+            start = i + 1;
+            continue;
+        }
+        let end_of_token = match chars.peek() {
+            Some((_, nxt)) => {
+                nxt.is_ascii_digit() != c.is_ascii_digit() || !nxt.is_ascii_alphanumeric()
+            }
+            None => true,
+        };
+        if end_of_token {
+            out.push(&x[start..i + 1]);
+            start = i + 1;
+        }
+    }
+    Some(out)
+}
+
+// dates with the month name in any position, e.g., "25-Dec-2021", "Dec 25 2021",
+// "2021-DEC-25" and "25DEC21"
+fn named2date(x: &str, order: Order, pivot: i32) -> ParseResult {
+    let v = tokenize(x).ok_or(Reason::BadSeparator)?;
+    if v.len() != 3 {
+        return Err(Reason::BadFieldCount);
+    }
+    let pos = v
+        .iter()
+        .position(|i| !i.as_bytes()[0].is_ascii_digit())
+        .ok_or(Reason::BadToken)?;
+    let month = month_name(v[pos]).ok_or(Reason::BadToken)?;
+    let nums: Vec<&str> = v
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != pos)
+        .map(|(_, i)| *i)
+        .collect();
+    if !nums.iter().all(|i| i.as_bytes()[0].is_ascii_digit()) {
+        return Err(Reason::BadToken);
+    }
+    // the field with more than two digits is the year; otherwise, the year comes
+    // first only for the 'YMD' order without a leading month name
+    let year_first = match (nums[0].len() > 2, nums[1].len() > 2) {
+        (true, false) => true,
+        (false, true) => false,
+        _ => order == Order::Ymd && pos != 0,
+    };
+    let (y, d) = if year_first {
+        (nums[0], nums[1])
+    } else {
+        (nums[1], nums[0])
+    };
+    let pivot = if y.len() <= 2 { Some(pivot) } else { None };
+    fields2date(field(y)?, month, field(d)?, pivot)
+}
+
+// the value of exactly `n` ASCII digits
+fn digits(x: &str, n: usize) -> Option<i32> {
+    if x.len() == n && x.bytes().all(|b| b.is_ascii_digit()) {
+        x.parse().ok()
+    } else {
+        None
+    }
+}
+
+// ISO 8601 week dates, "2021-W52-6" or "2021W526"; None if it's not in the form
+fn isoweek2date(x: &str) -> Option<ParseResult> {
+    let (y, rest) = x.split_once(['W', 'w'])?;
+    let (y, w, d) = match (y.strip_suffix('-'), rest.split_once('-')) {
+        (Some(y), Some((w, d))) => (y, w, d),
+        (None, None) if rest.len() == 3 => (y, &rest[..2], &rest[2..]),
+        _ => return None,
+    };
+    let date = dateof::from_isoweek(digits(y, 4)?, digits(w, 2)? as u32, digits(d, 1)? as u32);
+    Some(date.ok_or(Reason::InvalidDate))
+}
+
+// ISO 8601 ordinal dates, "2021-359" or "2021359"; None if it's not in the form
+fn ordinal2date(y: &str, d: &str) -> Option<ParseResult> {
+    let date = dateof::from_yday(digits(y, 4)?, digits(d, 3)? as u32);
+    Some(date.ok_or(Reason::InvalidDate))
+}
+
+// period labels: "2021" (year), "202112" or "2021-12" (month), "2021Q4" or
+// "2021-Q4" (quarter), "2021H2" or "2021-H2" (half year) and "2021W52" or
+// "2021-W52" (ISO week); returns the first day of the period and the period
+pub fn label2period(x: &str) -> Option<(NaiveDate, Period)> {
+    if x.len() < 4 || !x.is_char_boundary(4) {
+        return None;
+    }
+    let (y, rest) = x.split_at(4);
+    let year = digits(y, 4)?;
+    let (rest, sep) = match rest.strip_prefix(['-', '/', '.', ' ']) {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+    if rest.is_empty() {
+        return if sep {
+            None
+        } else {
+            Some((NaiveDate::from_ymd_opt(year, 1, 1)?, Period::Year))
+        };
+    }
+    let (kind, n) = match rest.as_bytes()[0] {
+        b'0'..=b'9' if sep => (None, digits(rest, rest.len().min(2))?),
+        b'0'..=b'9' => (None, digits(rest, 2)?),
+        c if c.is_ascii_alphabetic() => (
+            Some(c.to_ascii_uppercase()),
+            digits(&rest[1..], rest.len() - 1)?,
+        ),
+        _ => return None,
+    };
+    let month = |m: i32| NaiveDate::from_ymd_opt(year, m as u32, 1);
+    match (kind, n) {
+        (None, 1..=12) => Some((month(n)?, Period::Month)),
+        (Some(b'Q'), 1..=4) => Some((month(n * 3 - 2)?, Period::Quarter)),
+        (Some(b'H'), 1..=2) => Some((month(n * 6 - 5)?, Period::Semiannual)),
+        (Some(b'W'), 1..=53) if rest.len() == 3 => {
+            Some((dateof::from_isoweek(year, n as u32, 1)?, Period::Week))
+        }
+        _ => None,
+    }
+}
+
+// partial dates, "2021" (year) or "2021-07" and "202107" (month); returns the
+// first day of the period and the period
+pub fn partial2period(x: &str) -> Option<(NaiveDate, Period)> {
+    label2period(x).filter(|(_, p)| matches!(p, Period::Year | Period::Month))
+}
+
+// converts the full-width forms, e.g., "２０２１／１２／２５", and the
+// ideographic space to ASCII
+fn to_halfwidth(x: &str) -> String {
+    x.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap(),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+// "2021年12月25日", "21年3月5日" or "2021年1月" (the first day of the month)
+fn cjk2date(x: &str, pivot: i32) -> ParseResult {
+    let (y, rest) = x.split_once('年').ok_or(Reason::BadFieldCount)?;
+    let (m, d) = rest.split_once('月').ok_or(Reason::BadFieldCount)?;
+    let d = if d.trim().is_empty() {
+        "1"
+    } else {
+        d.strip_suffix('日').ok_or(Reason::BadToken)?
+    };
+    let (y, m, d) = (y.trim(), m.trim(), d.trim());
+    let pivot = if y.len() <= 2 { Some(pivot) } else { None };
+    fields2date(field(y)?, field(m)?, field(d)?, pivot)
+}
+
+fn hms2time(h: i32, m: i32, s: i32, zone: Option<i32>) -> Option<Time> {
+    // allows "24:00:00" and the leap second, which is read as the 59th second
+    // so that only "24:00:00" reaches the next day
+    if (0..24).contains(&h) && (0..60).contains(&m) && (0..=60).contains(&s)
+        || (h, m, s) == (24, 0, 0)
+    {
+        Some(Time {
+            secs: h * 3600 + m * 60 + s.min(59),
+            zone,
+        })
+    } else {
+        None
+    }
+}
+
+// "Z", "+08", "+0800" or "-05:00"
+fn zone2secs(x: &str) -> Option<i32> {
+    // the byte offsets below need ASCII
+    if !x.is_ascii() {
+        return None;
+    }
+    if x.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+    let sign = match x.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let x = &x[1..];
+    let (h, m) = match x.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if x.len() == 4 => x.split_at(2),
+        None => (x, "00"),
+    };
+    match (digits(h, 2), digits(m, 2)) {
+        (Some(h), Some(m)) if h <= 14 && m < 60 => Some(sign * (h * 3600 + m * 60)),
+        _ => None,
+    }
+}
+
+// "13:45", "13:45:00", "13:45:00.123" or the compact "1345" and "134500",
+// optionally followed by the UTC offset
+fn str2time(x: &str) -> Option<Time> {
+    // the byte offsets below need ASCII
+    if !x.is_ascii() {
+        return None;
+    }
+    let (clock, zone) = match x.find(['Z', 'z', '+', '-']) {
+        Some(i) => (x[..i].trim_end(), Some(zone2secs(&x[i..])?)),
+        None => (x, None),
+    };
+    // the fraction of the second doesn't change the date
+    let clock = match clock.split_once(['.', ',']) {
+        Some((c, f)) if !f.is_empty() && f.bytes().all(|b| b.is_ascii_digit()) => c,
+        Some(_) => return None,
+        None => clock,
+    };
+    let (h, m, s) = if clock.contains(':') {
+        let v: Vec<&str> = clock.split(':').collect();
+        match v[..] {
+            [h, m] => (h, m, "00"),
+            [h, m, s] => (h, m, s),
+            _ => return None,
+        }
+    } else if clock.len() == 4 {
+        (&clock[..2], &clock[2..], "00")
+    } else if clock.len() == 6 {
+        (&clock[..2], &clock[2..4], &clock[4..])
+    } else {
+        return None;
+    };
+    let h = if h.len() == 1 {
+        digits(h, 1)
+    } else {
+        digits(h, 2)
+    };
+    hms2time(h?, digits(m, 2)?, digits(s, 2)?, zone)
+}
+
+// splits "2021-12-25 13:45:00", "2021-12-25T13:45:00Z" and "20211225134500" into
+// the date part and the time part
+fn split_time(x: &str) -> (&str, Option<&str>) {
+    let b = x.as_bytes();
+    if b.len() == 14 && b.iter().all(|b| b.is_ascii_digit()) {
+        return (&x[..8], Some(&x[8..]));
+    }
+    // the ISO 8601 "T" is surrounded by digits, which never happens in month
+    // names like "OCT"
+    let iso = (1..b.len().saturating_sub(1)).find(|&i| {
+        b[i].eq_ignore_ascii_case(&b'T') && b[i - 1].is_ascii_digit() && b[i + 1].is_ascii_digit()
+    });
+    if let Some(i) = iso {
+        return (&x[..i], Some(&x[i + 1..]));
+    }
+    // the time part begins after the last space before the first colon
+    if let Some(i) = x.find(':') {
+        if let Some(j) = x[..i].trim_end().rfind(' ') {
+            return (x[..j].trim_end(), Some(&x[j + 1..]));
+        }
+    }
+    (x, None)
+}
+
+// parses the date with an optional time part, which is returned separately
+pub fn str2datetime(x: &str, order: Order, pivot: i32) -> DateTimeResult {
+    if !x.is_ascii() {
+        let x = to_halfwidth(x);
+        return if x.contains('年') {
+            let (date, time) = split_time(&x);
+            let time = match time {
+                Some(t) => Some(str2time(t).ok_or(Reason::BadTime)?),
+                None => None,
+            };
+            end_of_day(cjk2date(date, pivot)?, time)
+        } else if x.is_ascii() {
+            str2datetime(&x, order, pivot)
+        } else {
+            Err(Reason::BadSeparator)
+        };
+    }
+    let (date, time) = split_time(x);
+    let time = match time {
+        Some(t) => Some(str2time(t).ok_or(Reason::BadTime)?),
+        None => None,
+    };
+    end_of_day(ascii2date(date, order, pivot)?, time)
+}
+
+// "24:00:00" is the end of the day, i.e., the midnight of the next day
+fn end_of_day(date: NaiveDate, time: Option<Time>) -> DateTimeResult {
+    match time {
+        Some(t) if t.secs == 86400 => {
+            let date = date.succ_opt().ok_or(Reason::OutOfRange)?;
+            Ok((date, Some(Time { secs: 0, ..t })))
+        }
+        _ => Ok((date, time)),
+    }
+}
+
+// the calendar date at the UTC offset (in seconds) of the timestamp, where the
+// time without its own UTC offset is regarded as UTC
+pub fn at_offset(date: NaiveDate, time: Option<Time>, offset: i32) -> ParseResult {
+    let Some(time) = time else {
+        return Ok(date);
+    };
+    let secs = time.secs as i64 - time.zone.unwrap_or(0) as i64 + offset as i64;
+    chrono::Duration::try_days(secs.div_euclid(86400))
+        .and_then(|days| date.checked_add_signed(days))
+        .ok_or(Reason::OutOfRange)
+}
+
+// the date of a token found by scan_dates(): CJK dates, eight-digit compact
+// dates (or fourteen-digit timestamps), or three separated fields with a
+// four-digit one; looser forms like "1.2.3" are too often version numbers
+fn token2date(x: &str, order: Order, pivot: i32) -> Option<NaiveDate> {
+    if x.contains('年') {
+        return cjk2date(x, pivot).ok();
+    }
+    if x.bytes().all(|b| b.is_ascii_digit()) {
+        return match x.len() {
+            8 | 14 => str2datetime(x, order, pivot).ok().map(|(date, _)| date),
+            _ => None,
+        };
+    }
+    let v: Vec<&str> = x.split(['-', '.', '/']).collect();
+    if v.len() != 3 || !v.iter().any(|f| f.len() == 4) {
+        return None;
+    }
+    ascii2date(x, order, pivot).ok()
+}
+
+// finds the date-like tokens in the text, e.g., "report_20211225_v2.csv",
+// "NAV 2021-12-25 final" or "持仓2021年12月25日.xlsx"; returns the dates with the
+// zero-based positions (in characters) of the first and the last characters
+pub fn scan_dates(x: &str, order: Order, pivot: i32) -> Vec<(NaiveDate, usize, usize)> {
+    // the full-width forms are converted one by one, keeping the positions
+    let chars: Vec<char> = to_halfwidth(x).chars().collect();
+    let is_sep = |c: char| matches!(c, '-' | '.' | '/');
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        // a token begins at a digit and spans the digits, separators and CJK
+        // markers, without the trailing separators
+        let mut j = i;
+        while j < chars.len()
+            && (chars[j].is_ascii_digit() || is_sep(chars[j]) || "年月日".contains(chars[j]))
+        {
+            j += 1;
+        }
+        let mut end = j;
+        while is_sep(chars[end - 1]) {
+            end -= 1;
+        }
+        let token: String = chars[i..end].iter().collect();
+        if let Some(date) = token2date(&token, order, pivot) {
+            out.push((date, i, end - 1));
+        } else if !token.contains(['年', '月', '日']) {
+            out.extend(scan_windows(&chars[..end], i, order, pivot));
+        }
+        i = j;
+    }
+    out
+}
+
+// the dates inside a token that fails as a whole, e.g., "2021-12-25-1" or
+// "2.2021-12-25": tries the windows of three fields joined by the same separator
+// with the four-digit one at either end, and the single fields of eight or
+// fourteen digits, from the left
+fn scan_windows(
+    chars: &[char],
+    start: usize,
+    order: Order,
+    pivot: i32,
+) -> Vec<(NaiveDate, usize, usize)> {
+    // the digit runs as [begin, end)
+    let mut fields: Vec<(usize, usize)> = Vec::new();
+    let mut i = start;
+    while i < chars.len() {
+        if chars[i].is_ascii_digit() {
+            let begin = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            fields.push((begin, i));
+        } else {
+            i += 1;
+        }
+    }
+    let text = |b: usize, e: usize| chars[b..e].iter().collect::<String>();
+    let mut out = Vec::new();
+    let mut k = 0;
+    while k < fields.len() {
+        if let Some(w) = fields.get(k..k + 3) {
+            let joined =
+                w[1].0 == w[0].1 + 1 && w[2].0 == w[1].1 + 1 && chars[w[0].1] == chars[w[1].1];
+            let year_at_end = w[0].1 - w[0].0 == 4 || w[2].1 - w[2].0 == 4;
+            if joined && year_at_end {
+                if let Some(date) = token2date(&text(w[0].0, w[2].1), order, pivot) {
+                    out.push((date, w[0].0, w[2].1 - 1));
+                    k += 3;
+                    continue;
+                }
+            }
+        }
+        let (b, e) = fields[k];
+        if matches!(e - b, 8 | 14) {
+            if let Some(date) = token2date(&text(b, e), order, pivot) {
+                out.push((date, b, e - 1));
+            }
+        }
+        k += 1;
+    }
+    out
+}
+
+// the strptime-like format of the date that is parsed in the order, e.g.,
+// "%d/%m/%y", "%Y%m%d" or "%b %d, %Y", ignoring the time part; None for the
+// forms without such a format, e.g., ISO week dates or CJK dates
+pub fn describe(x: &str, order: Order) -> Option<String> {
+    let (x, _) = split_time(x);
+    if !x.is_ascii() {
+        return None;
+    }
+    let year = |t: &str| if t.len() > 2 { "%Y" } else { "%y" };
+    if x.bytes().all(|b| b.is_ascii_digit()) {
+        if x.len() != 6 && x.len() != 8 {
+            return None;
+        }
+        let y = year(&x[..x.len() - 4]);
+        return Some(match order {
+            Order::Ymd => format!("{}%m%d", y),
+            Order::Dmy => format!("%d%m{}", y),
+            Order::Mdy => format!("%m%d{}", y),
+        });
+    }
+    let v = tokenize(x)?;
+    if v.len() != 3 {
+        return None;
+    }
+    let specs = match v.iter().position(|t| t.as_bytes()[0].is_ascii_alphabetic()) {
+        None => match order {
+            Order::Ymd => [year(v[0]), "%m", "%d"],
+            Order::Dmy => ["%d", "%m", year(v[2])],
+            Order::Mdy => ["%m", "%d", year(v[2])],
+        },
+        // the same rule as named2date()
+        Some(pos) => {
+            let nums: Vec<usize> = (0..3).filter(|i| *i != pos).collect();
+            let year_first = match (v[nums[0]].len() > 2, v[nums[1]].len() > 2) {
+                (true, false) => true,
+                (false, true) => false,
+                _ => order == Order::Ymd && pos != 0,
+            };
+            let (y, d) = if year_first {
+                (nums[0], nums[1])
+            } else {
+                (nums[1], nums[0])
+            };
+            let mut specs = [""; 3];
+            specs[pos] = if v[pos].len() == 3 { "%b" } else { "%B" };
+            specs[y] = year(v[y]);
+            specs[d] = "%d";
+            specs
+        }
+    };
+    // only separators lie between the tokens
+    let mut out = String::new();
+    let mut end = 0;
+    for (t, spec) in v.iter().zip(specs) {
+        let start = end + x[end..].find(t)?;
+        out.push_str(&x[end..start]);
+        out.push_str(spec);
+        end = start + t.len();
+    }
+    out.push_str(&x[end..]);
+    Some(out)
+}
+
+fn ascii2date(x: &str, order: Order, pivot: i32) -> ParseResult {
+    match x.parse::<i32>() {
+        // a seven-digit value can only be an ordinal date in the 'YMD' order;
+        // leading zeros don't count, so "0980308" is still 1998-03-08
+        Ok(v)
+            if order == Order::Ymd
+                && (1_000_000..10_000_000).contains(&v)
+                && x.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            dateof::from_yday(v / 1000, (v % 1000) as u32).ok_or(Reason::InvalidDate)
+        }
+        Ok(v) => int2date(v, order, Some(pivot)),
+        Err(_) if x.bytes().any(|b| b.is_ascii_alphabetic()) => match isoweek2date(x) {
+            Some(date) => date,
+            None => named2date(x, order, pivot),
+        },
+        Err(_)
+            if x.bytes()
+                .any(|b| !b.is_ascii_digit() && !b"-./ ".contains(&b)) =>
+        {
+            Err(Reason::BadSeparator)
+        }
+        Err(_) => {
+            let v: Vec<&str> = x.split(&['-', '.', '/', ' '][..]).collect();
+            if v.len() == 2 {
+                ordinal2date(v[0], v[1]).unwrap_or(Err(Reason::BadFieldCount))
+            } else if v.len() == 3 {
+                let (y, m, d) = match order {
+                    Order::Ymd => (v[0], v[1], v[2]),
+                    Order::Dmy => (v[2], v[1], v[0]),
+                    Order::Mdy => (v[2], v[0], v[1]),
+                };
+                let pivot = if y.len() <= 2 { Some(pivot) } else { None };
+                fields2date(field(y)?, field(m)?, field(d)?, pivot)
+            } else {
+                Err(Reason::BadFieldCount)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn str2date(x: &str, order: Order, pivot: i32) -> ParseResult {
+        str2datetime(x, order, pivot).map(|(date, _)| date)
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(
+            int2date(980308, Order::Ymd, Some(DEFAULT_PIVOT)).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            int2date(980308, Order::Ymd, None).unwrap(),
+            NaiveDate::from_ymd_opt(98, 3, 8).unwrap()
+        );
+        assert_eq!(
+            int2date(50308, Order::Ymd, Some(DEFAULT_PIVOT)).unwrap(),
+            NaiveDate::from_ymd_opt(2005, 3, 8).unwrap()
+        );
+        assert_eq!(
+            int2date(19980308, Order::Ymd, Some(DEFAULT_PIVOT)).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            int2date(21050308, Order::Ymd, Some(DEFAULT_PIVOT)).unwrap(),
+            NaiveDate::from_ymd_opt(2105, 3, 8).unwrap()
+        );
+        assert_eq!(int2date(980230, Order::Ymd, Some(DEFAULT_PIVOT)).ok(), None);
+        assert_eq!(
+            int2date(19980230, Order::Ymd, Some(DEFAULT_PIVOT)).ok(),
+            None
+        );
+        assert_eq!(int2date(22, Order::Ymd, Some(DEFAULT_PIVOT)).ok(), None);
+        assert_eq!(
+            int2date(2201010, Order::Ymd, Some(DEFAULT_PIVOT)).unwrap(),
+            NaiveDate::from_ymd_opt(220, 10, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn compact_integers() {
+        let date = NaiveDate::from_ymd_opt(2021, 1, 5).unwrap();
+        assert_eq!(date2int(&date, Compact::Yyyymmdd), Some(20210105));
+        assert_eq!(date2int(&date, Compact::Yymmdd), Some(210105));
+        assert_eq!(date2int(&date, Compact::Yyyymm), Some(202101));
+        let date = NaiveDate::from_ymd_opt(10000, 1, 1).unwrap();
+        assert_eq!(date2int(&date, Compact::Yyyymmdd), None);
+        let date = NaiveDate::from_ymd_opt(-1, 1, 1).unwrap();
+        assert_eq!(date2int(&date, Compact::Yyyymmdd), None);
+        // round-trips with int2date()
+        for days in -30000..30000 {
+            let date = NaiveDate::from_num_days_from_ce_opt(730000 + days).unwrap();
+            let x = date2int(&date, Compact::Yyyymmdd).unwrap();
+            assert_eq!(int2date(x, Order::Ymd, Some(DEFAULT_PIVOT)), Ok(date));
+        }
+    }
+
+    #[test]
+    fn pivot() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(expand_year(70, 1970), Some(1970));
+        assert_eq!(expand_year(69, 1970), Some(2069));
+        assert_eq!(expand_year(65, 1950), Some(1965));
+        assert_eq!(expand_year(49, 1950), Some(2049));
+        assert_eq!(expand_year(0, 2000), Some(2000));
+        assert_eq!(expand_year(98, i32::MIN), Some(i32::MIN + 46));
+        assert_eq!(expand_year(98, i32::MAX), None);
+        assert_eq!(
+            int2date(980308, Order::Ymd, Some(i32::MAX)),
+            Err(Reason::OutOfRange)
+        );
+        assert_eq!(
+            int2date(980308, Order::Ymd, Some(i32::MIN)),
+            Err(Reason::InvalidDate)
+        );
+        assert_eq!(
+            int2date(650308, Order::Ymd, Some(1950)).ok(),
+            fromymd(1965, 3, 8)
+        );
+        assert_eq!(
+            int2date(750308, Order::Ymd, Some(1950)).ok(),
+            fromymd(1975, 3, 8)
+        );
+        assert_eq!(
+            int2date(720308, Order::Ymd, Some(2000)).ok(),
+            fromymd(2072, 3, 8)
+        );
+        assert_eq!(
+            int2date(19650308, Order::Ymd, Some(2000)).ok(),
+            fromymd(1965, 3, 8)
+        );
+        assert_eq!(
+            dbl2date(650308., Order::Ymd, 1950).ok(),
+            fromymd(1965, 3, 8)
+        );
+        assert_eq!(
+            str2date("650308", Order::Ymd, 1950).ok(),
+            fromymd(1965, 3, 8)
+        );
+        assert_eq!(
+            str2date("65-3-8", Order::Ymd, 1950).ok(),
+            fromymd(1965, 3, 8)
+        );
+        assert_eq!(
+            str2date("72/03/08", Order::Ymd, 2000).ok(),
+            fromymd(2072, 3, 8)
+        );
+        assert_eq!(
+            str2date("1965-03-08", Order::Ymd, 2000).ok(),
+            fromymd(1965, 3, 8)
+        );
+    }
+
+    #[test]
+    fn doubles() {
+        assert_eq!(
+            dbl2date(980308., Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            dbl2date(050308., Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(2005, 3, 8).unwrap()
+        );
+        assert_eq!(
+            dbl2date(19980308., Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            dbl2date(21050308., Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(2105, 3, 8).unwrap()
+        );
+        assert_eq!(dbl2date(980230., Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(dbl2date(19980230., Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(dbl2date(980230.1, Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(dbl2date(2201310., Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(dbl2date(220101.5, Order::Ymd, DEFAULT_PIVOT).ok(), None);
+    }
+    #[test]
+    fn strings() {
+        assert_eq!(
+            str2date("980308", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("98.3.08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("98.3.8", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("98.03.08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("98/03/08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("98-03-08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+
+        assert_eq!(
+            str2date("220102", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
+        );
+        assert_eq!(
+            str2date("22.01.02", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
+        );
+        assert_eq!(
+            str2date("22/01/02", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
+        );
+        assert_eq!(
+            str2date("22-01-02", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
+        );
+
+        assert_eq!(
+            str2date("19980308", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("1998.03.08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("1998/03/08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+        assert_eq!(
+            str2date("1998-03-08", Order::Ymd, DEFAULT_PIVOT).unwrap(),
+            NaiveDate::from_ymd_opt(1998, 3, 8).unwrap()
+        );
+
+        assert_eq!(str2date("98308", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("980338", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("9a0308", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+    }
+
+    #[test]
+    fn day_first() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(
+            int2date(251221, Order::Dmy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            int2date(25122021, Order::Dmy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            int2date(5122021, Order::Dmy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(2021, 12, 5)
+        );
+        assert_eq!(
+            int2date(51298, Order::Dmy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(1998, 12, 5)
+        );
+        assert_eq!(int2date(311121, Order::Dmy, Some(DEFAULT_PIVOT)).ok(), None);
+        assert_eq!(
+            dbl2date(251221., Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(dbl2date(251221.5, Order::Dmy, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(
+            str2date("251221", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("05122021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 5)
+        );
+        assert_eq!(
+            str2date("25/12/2021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("25.12.21", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("5-1-98", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(1998, 1, 5)
+        );
+        assert_eq!(
+            str2date("25/12/0021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(21, 12, 25)
+        );
+        assert_eq!(str2date("12/25/2021", Order::Dmy, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(
+            str2date("25/12/65", Order::Dmy, 1950).ok(),
+            fromymd(1965, 12, 25)
+        );
+    }
+
+    #[test]
+    fn month_first() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(
+            int2date(122521, Order::Mdy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            int2date(12252021, Order::Mdy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            int2date(1052021, Order::Mdy, Some(DEFAULT_PIVOT)).ok(),
+            fromymd(2021, 1, 5)
+        );
+        assert_eq!(
+            dbl2date(122521., Order::Mdy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("122521", Order::Mdy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("12/25/21", Order::Mdy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("12-25-2021", Order::Mdy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 25)
+        );
+        assert_eq!(
+            str2date("1 5 2021", Order::Mdy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 1, 5)
+        );
+        assert_eq!(str2date("25/12/2021", Order::Mdy, DEFAULT_PIVOT).ok(), None);
+    }
+
+    #[test]
+    fn month_names() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        let expected = fromymd(2021, 12, 25);
+        assert_eq!(month_name("Dec"), Some(12));
+        assert_eq!(month_name("DECEMBER"), Some(12));
+        assert_eq!(month_name("sept"), Some(9));
+        assert_eq!(month_name("ma"), None);
+        assert_eq!(month_name("decem"), None);
+        assert_eq!(
+            str2date("25-Dec-2021", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("Dec 25 2021", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("Dec 25, 2021", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("December 25, 2021", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021-DEC-25", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021 december 25", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("25DEC21", Order::Dmy, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("25DEC2021", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("21-Dec-25", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("Dec 25 21", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("25 Dec 2021", Order::Mdy, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("25-Dec-65", Order::Dmy, 1950).ok(),
+            fromymd(1965, 12, 25)
+        );
+        assert_eq!(
+            str2date("2021-Sept-05", Order::Ymd, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 9, 5)
+        );
+        assert_eq!(
+            str2date("31-Nov-2021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            None
+        );
+        assert_eq!(
+            str2date("25-Dex-2021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            None
+        );
+        assert_eq!(str2date("25-Dec-Jan", Order::Dmy, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("Dec 2021", Order::Dmy, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(
+            str2date("25_Dec_2021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            None
+        );
+    }
+
+    #[test]
+    fn cjk() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        let expected = fromymd(2021, 12, 25);
+        assert_eq!(
+            str2date("2021年12月25日", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021 年 12 月 25 日", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021年1月", Order::Ymd, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 1, 1)
+        );
+        assert_eq!(
+            str2date("21年3月5日", Order::Ymd, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 3, 5)
+        );
+        assert_eq!(
+            str2date("65年3月5日", Order::Ymd, 1950).ok(),
+            fromymd(1965, 3, 5)
+        );
+        assert_eq!(
+            str2date("２０２１年１２月２５日", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("２０２１／１２／２５", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("２０２１－１２－２５", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("２０２１　１２　２５", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("２５／１２／２０２１", Order::Dmy, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("２０２１１２２５", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021年2月30日", Order::Ymd, DEFAULT_PIVOT).ok(),
+            None
+        );
+        assert_eq!(
+            str2date("2021年12月25", Order::Ymd, DEFAULT_PIVOT).ok(),
+            None
+        );
+        assert_eq!(str2date("2021年", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(
+            str2date("2021年12月25日星期六", Order::Ymd, DEFAULT_PIVOT).ok(),
+            None
+        );
+        assert_eq!(str2date("2021—12—25", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+    }
+
+    #[test]
+    fn iso_week_and_ordinal() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        let expected = fromymd(2021, 12, 25);
+        assert_eq!(
+            str2date("2021-W51-6", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021W516", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021w516", Order::Dmy, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021-W52-6", Order::Ymd, DEFAULT_PIVOT).ok(),
+            fromymd(2022, 1, 1)
+        );
+        assert_eq!(
+            str2date("2021-359", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        assert_eq!(
+            str2date("2021359", Order::Ymd, DEFAULT_PIVOT).ok(),
+            expected
+        );
+        // numbers and zero-padded strings are not ordinal dates
+        assert_eq!(dbl2date(2021359., Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(
+            str2date("0980308", Order::Ymd, DEFAULT_PIVOT).ok(),
+            fromymd(1998, 3, 8)
+        );
+        assert_eq!(
+            str2date("2020-366", Order::Mdy, DEFAULT_PIVOT).ok(),
+            fromymd(2020, 12, 31)
+        );
+        assert_eq!(str2date("2021-W53-1", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("2021-W51-8", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("2021-W516", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("2021W51", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("21-W51-6", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("2021-366", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("2021-36", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        assert_eq!(str2date("2021366", Order::Ymd, DEFAULT_PIVOT).ok(), None);
+        // seven digits are 'DMMYYYY' in the day-first order
+        assert_eq!(
+            str2date("5122021", Order::Dmy, DEFAULT_PIVOT).ok(),
+            fromymd(2021, 12, 5)
+        );
+    }
+
+    #[test]
+    fn partial_dates() {
+        let fromymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            partial2period("2021"),
+            Some((fromymd(2021, 1, 1), Period::Year))
+        );
+        assert_eq!(
+            partial2period("202107"),
+            Some((fromymd(2021, 7, 1), Period::Month))
+        );
+        assert_eq!(
+            partial2period("2021-7"),
+            Some((fromymd(2021, 7, 1), Period::Month))
+        );
+        assert_eq!(partial2period("2021Q3"), None);
+        assert_eq!(partial2period("2021W30"), None);
+        assert_eq!(partial2period("2021-07-01"), None);
+        assert_eq!(partial2period("202113"), None);
+    }
+
+    #[test]
+    fn labels() {
+        let fromymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let label = label2period;
+        assert_eq!(label("2021"), Some((fromymd(2021, 1, 1), Period::Year)));
+        assert_eq!(label("202112"), Some((fromymd(2021, 12, 1), Period::Month)));
+        assert_eq!(
+            label("2021-12"),
+            Some((fromymd(2021, 12, 1), Period::Month))
+        );
+        assert_eq!(label("2021/1"), Some((fromymd(2021, 1, 1), Period::Month)));
+        assert_eq!(
+            label("2021Q4"),
+            Some((fromymd(2021, 10, 1), Period::Quarter))
+        );
+        assert_eq!(
+            label("2021-q4"),
+            Some((fromymd(2021, 10, 1), Period::Quarter))
+        );
+        assert_eq!(
+            label("2021H2"),
+            Some((fromymd(2021, 7, 1), Period::Semiannual))
+        );
+        assert_eq!(
+            label("2021-H1"),
+            Some((fromymd(2021, 1, 1), Period::Semiannual))
+        );
+        assert_eq!(
+            label("2021-W52"),
+            Some((fromymd(2021, 12, 27), Period::Week))
+        );
+        assert_eq!(label("2021W01"), Some((fromymd(2021, 1, 4), Period::Week)));
+        assert_eq!(label("202113"), None);
+        assert_eq!(label("20211"), None);
+        assert_eq!(label("2021-"), None);
+        assert_eq!(label("2021Q5"), None);
+        assert_eq!(label("2021H3"), None);
+        assert_eq!(label("2021W1"), None);
+        assert_eq!(label("2021-W53"), None);
+        assert_eq!(label("2021-X1"), None);
+        assert_eq!(label("21Q4"), None);
+        assert_eq!(label("2021Q"), None);
+        assert_eq!(label("年2021"), None);
+        assert_eq!(label("2021-年1"), None);
+    }
+
+    #[test]
+    fn datetimes() {
+        let ymd = |x| str2date(x, Order::Ymd, DEFAULT_PIVOT).ok();
+        let date = NaiveDate::from_ymd_opt(2021, 12, 25);
+        assert_eq!(ymd("2021-12-25 13:45:00"), date);
+        assert_eq!(ymd("2021-12-25  13:45"), date);
+        assert_eq!(ymd("2021-12-25T13:45:00Z"), date);
+        assert_eq!(ymd("2021-12-25T13:45:00.123+08:00"), date);
+        assert_eq!(ymd("2021-12-25t134500-0500"), date);
+        assert_eq!(ymd("20211225T1345"), date);
+        assert_eq!(ymd("20211225134500"), date);
+        assert_eq!(ymd("2021/12/24 24:00:00"), date);
+        assert_eq!(ymd("2021/12/25 23:59:60"), date);
+        assert_eq!(ymd("Dec 25, 2021 13:45"), date);
+        assert_eq!(ymd("25OCT2021"), NaiveDate::from_ymd_opt(2021, 10, 25));
+        assert_eq!(ymd("2021年12月25日 13:45"), date);
+        assert_eq!(
+            str2date("25/12/2021 1:45", Order::Dmy, DEFAULT_PIVOT).ok(),
+            date
+        );
+        assert_eq!(
+            dbl2datetime(20211225134500.0, Order::Ymd, DEFAULT_PIVOT),
+            Ok((
+                date.unwrap(),
+                Some(Time {
+                    secs: 13 * 3600 + 45 * 60,
+                    zone: None
+                })
+            ))
+        );
+        assert_eq!(
+            dbl2datetime(20211225.0, Order::Ymd, DEFAULT_PIVOT),
+            Ok((date.unwrap(), None))
+        );
+
+        let at = |x, offset| {
+            let (date, time) = str2datetime(x, Order::Ymd, DEFAULT_PIVOT).unwrap();
+            at_offset(date, time, offset * 3600).ok()
+        };
+        let date = |d| NaiveDate::from_ymd_opt(2021, 12, d);
+        assert_eq!(at("2021-12-25T20:00:00-05:00", 8), date(26));
+        assert_eq!(at("2021-12-25T20:00:00-05:00", -5), date(25));
+        assert_eq!(at("2021-12-25T02:00:00+08:00", 0), date(24));
+        assert_eq!(at("2021-12-25 23:30:00", 1), date(26));
+        assert_eq!(at("2021-12-25 00:30:00", -1), date(24));
+        assert_eq!(at("2021-12-25", -12), date(25));
+        // the same date with or without the offset
+        assert_eq!(at("2021-12-24 24:00:00", 0), date(25));
+        assert_eq!(at("2021-12-25 23:59:60", 0), date(25));
+        assert_eq!(
+            dbl2datetime(20211224240000.0, Order::Ymd, DEFAULT_PIVOT),
+            Ok((
+                date(25).unwrap(),
+                Some(Time {
+                    secs: 0,
+                    zone: None
+                })
+            ))
+        );
+        let (d, time) = str2datetime("2021-12-25 23:30:00", Order::Ymd, DEFAULT_PIVOT).unwrap();
+        assert_eq!(
+            at_offset(d, time, i32::MAX),
+            d.checked_add_days(chrono::Days::new(24856))
+                .ok_or(Reason::OutOfRange)
+        );
+        let d = NaiveDate::MAX;
+        assert_eq!(at_offset(d, time, i32::MAX), Err(Reason::OutOfRange));
+        assert_eq!(
+            at_offset(NaiveDate::MIN, time, i32::MIN),
+            Err(Reason::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let f = |x, order| describe(x, order);
+        assert_eq!(f("01/02/03", Order::Dmy).as_deref(), Some("%d/%m/%y"));
+        assert_eq!(f("01/02/2003", Order::Mdy).as_deref(), Some("%m/%d/%Y"));
+        assert_eq!(f("2021-12-25", Order::Ymd).as_deref(), Some("%Y-%m-%d"));
+        assert_eq!(
+            f("2021-12-25 13:45", Order::Ymd).as_deref(),
+            Some("%Y-%m-%d")
+        );
+        assert_eq!(f("20211225", Order::Ymd).as_deref(), Some("%Y%m%d"));
+        assert_eq!(f("251221", Order::Dmy).as_deref(), Some("%d%m%y"));
+        assert_eq!(f("12252021", Order::Mdy).as_deref(), Some("%m%d%Y"));
+        assert_eq!(f("Dec 25, 2021", Order::Ymd).as_deref(), Some("%b %d, %Y"));
+        assert_eq!(f("25DEC21", Order::Dmy).as_deref(), Some("%d%b%y"));
+        assert_eq!(f("21-December-25", Order::Ymd).as_deref(), Some("%y-%B-%d"));
+        assert_eq!(f("2021-W52-6", Order::Ymd), None);
+        assert_eq!(f("2021-359", Order::Ymd), None);
+        assert_eq!(f("2021年12月25日", Order::Ymd), None);
+        assert_eq!(f("2021359", Order::Ymd), None);
+    }
+
+    #[test]
+    fn scanned_dates() {
+        let date = NaiveDate::from_ymd_opt(2021, 12, 25).unwrap();
+        let scan = |x| scan_dates(x, Order::Ymd, DEFAULT_PIVOT);
+        assert_eq!(scan("report_20211225_v2.csv"), [(date, 7, 14)]);
+        assert_eq!(scan("NAV 2021-12-25 final"), [(date, 4, 13)]);
+        assert_eq!(scan("NAV 2021-12-25."), [(date, 4, 13)]);
+        assert_eq!(scan("持仓2021年12月25日.xlsx"), [(date, 2, 12)]);
+        assert_eq!(scan("持仓２０２１年１２月２５日"), [(date, 2, 12)]);
+        assert_eq!(scan("log_20211225134500.txt"), [(date, 4, 17)]);
+        assert_eq!(
+            scan("from 2021/12/25 to 2022.01.01"),
+            [
+                (date, 5, 14),
+                (NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(), 19, 28)
+            ]
+        );
+        assert_eq!(
+            scan_dates("due 25/12/2021", Order::Dmy, DEFAULT_PIVOT),
+            [(date, 4, 13)]
+        );
+        assert_eq!(scan("data_2021-12-25-1.csv"), [(date, 5, 14)]);
+        assert_eq!(scan("v2.2021-12-25"), [(date, 3, 12)]);
+        assert_eq!(scan("v2.20211225.txt"), [(date, 3, 10)]);
+        assert_eq!(scan("1.2021.12.25"), [(date, 2, 11)]);
+        assert_eq!(scan("v1.2021-12"), []);
+        assert_eq!(scan("v1.2.3 build 12345678 at 211225"), []);
+        assert_eq!(scan("2021-13-25"), []);
+        assert_eq!(scan(""), []);
+    }
+
+    #[test]
+    fn reasons() {
+        let ymd = |x| str2date(x, Order::Ymd, DEFAULT_PIVOT).unwrap_err();
+        assert_eq!(
+            dbl2date(980308.5, Order::Ymd, DEFAULT_PIVOT),
+            Err(Reason::NonInteger)
+        );
+        assert_eq!(
+            dbl2date(1e10, Order::Ymd, DEFAULT_PIVOT),
+            Err(Reason::OutOfRange)
+        );
+        assert_eq!(
+            dbl2date(f64::INFINITY, Order::Ymd, DEFAULT_PIVOT),
+            Err(Reason::OutOfRange)
+        );
+        assert_eq!(
+            int2date(980230, Order::Ymd, Some(DEFAULT_PIVOT)),
+            Err(Reason::InvalidDate)
+        );
+        assert_eq!(ymd("980230"), Reason::InvalidDate);
+        assert_eq!(ymd("1998-02-30"), Reason::InvalidDate);
+        assert_eq!(ymd("1998-13-01"), Reason::InvalidDate);
+        assert_eq!(ymd("30-Feb-1998"), Reason::InvalidDate);
+        assert_eq!(ymd("2021-W53-1"), Reason::InvalidDate);
+        assert_eq!(ymd("2021-366"), Reason::InvalidDate);
+        assert_eq!(ymd("2021年2月30日"), Reason::InvalidDate);
+        assert_eq!(ymd("1998_02_03"), Reason::BadSeparator);
+        assert_eq!(ymd("1998-02-03!"), Reason::BadSeparator);
+        assert_eq!(ymd("1998_Feb_03"), Reason::BadSeparator);
+        assert_eq!(ymd("1998—02—03"), Reason::BadSeparator);
+        assert_eq!(ymd("1998//02/03"), Reason::BadFieldCount);
+        assert_eq!(ymd("1998-02"), Reason::BadFieldCount);
+        assert_eq!(ymd("Feb 1998"), Reason::BadFieldCount);
+        assert_eq!(ymd("2021年"), Reason::BadFieldCount);
+        assert_eq!(ymd(""), Reason::BadFieldCount);
+        assert_eq!(ymd("1998-Fex-03"), Reason::BadToken);
+        assert_eq!(ymd("9a0308"), Reason::BadToken);
+        assert_eq!(ymd("1998-02-"), Reason::BadToken);
+        assert_eq!(ymd("99999999999-02-03"), Reason::BadToken);
+        assert_eq!(ymd("2021-12-25 25:00"), Reason::BadTime);
+        assert_eq!(ymd("2021-12-25T13:45:00+8"), Reason::BadTime);
+        assert_eq!(ymd("2021-12-25 1:45 PM"), Reason::BadTime);
+        assert_eq!(ymd("20211225250000"), Reason::BadTime);
+        // the non-ASCII time parts of CJK dates
+        assert_eq!(ymd("2021年1月1T1年"), Reason::BadTime);
+        assert_eq!(ymd("2021年1月1日 1時:00"), Reason::BadTime);
+        assert_eq!(ymd("2021年1月1T12+年"), Reason::BadTime);
+        assert_eq!(ymd("2021-12-32 13:45"), Reason::InvalidDate);
+    }
+}
@@ -28,7 +28,7 @@ pub fn add_months(ref_date: &NaiveDate, months: i32) -> NaiveDate {
     .unwrap()
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Period {
     Year,
     Semiannual,
@@ -2,7 +2,7 @@ use chrono::{Datelike, NaiveDate};
 use extendr_api::prelude::*;
 
 // The days from 1970-1-1 (R's first date) to CE (1-1-0)
-const R_DATE_FROM_CE: i32 = 719163;
+pub const R_DATE_FROM_CE: i32 = 719163;
 
 fn days_since_epoch_to_date(days: i32) -> Option<NaiveDate> {
     days.checked_add(R_DATE_FROM_CE)
@@ -22,7 +22,145 @@ fn real_days_since_epoch_to_date(days: f64) -> Option<NaiveDate> {
     days_since_epoch_to_date(days as i32)
 }
 
+// the epochs and units of the dates in the statistical software
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Epoch {
+    // days since 1960-01-01
+    Sas,
+    // Stata's %td (days), %tm (months) and %tq (quarters) since 1960-01-01
+    StataTd,
+    StataTm,
+    StataTq,
+    // seconds since 1582-10-14, the start of the Gregorian calendar
+    Spss,
+    // MATLAB's datenum, days since 0000-01-00
+    Matlab,
+}
+
+pub fn to_epoch(x: &str) -> Option<Epoch> {
+    match x {
+        "sas" => Some(Epoch::Sas),
+        "stata_td" => Some(Epoch::StataTd),
+        "stata_tm" => Some(Epoch::StataTm),
+        "stata_tq" => Some(Epoch::StataTq),
+        "spss" => Some(Epoch::Spss),
+        "matlab" => Some(Epoch::Matlab),
+        _ => None,
+    }
+}
+
+// The days from 1960-01-01 (SAS and Stata's day 0) to 1970-01-01
+const SAS_TO_R_DAYS: i32 = 3653;
+// The days from 1582-10-14 (SPSS's second 0) to 1970-01-01
+const SPSS_TO_R_DAYS: i32 = 141428;
+// datenum(1970, 1, 1) in MATLAB
+const MATLAB_TO_R_DAYS: i32 = 719529;
+const SECS_PER_DAY: f64 = 86400.0;
+
+// the first day of the month that is `n * months` months after 1960-01
+fn months_since_1960_to_date(n: f64, months: i64) -> Option<NaiveDate> {
+    if !n.is_finite() {
+        return None;
+    }
+    let n = n.floor();
+    if n < i32::MIN as f64 || n > i32::MAX as f64 {
+        return None;
+    }
+    let months = n as i64 * months;
+    let year = i32::try_from(1960 + months.div_euclid(12)).ok()?;
+    NaiveDate::from_ymd_opt(year, months.rem_euclid(12) as u32 + 1, 1)
+}
+
+// the fractional part, e.g., the time of the day, is dropped
+pub fn epoch2date(x: f64, epoch: Epoch) -> Option<NaiveDate> {
+    match epoch {
+        Epoch::Sas | Epoch::StataTd => real_days_since_epoch_to_date(x - SAS_TO_R_DAYS as f64),
+        Epoch::StataTm => months_since_1960_to_date(x, 1),
+        Epoch::StataTq => months_since_1960_to_date(x, 3),
+        Epoch::Spss => real_days_since_epoch_to_date(x / SECS_PER_DAY - SPSS_TO_R_DAYS as f64),
+        Epoch::Matlab => real_days_since_epoch_to_date(x - MATLAB_TO_R_DAYS as f64),
+    }
+}
+
+// the months and quarters that the date falls in for Stata's %tm and %tq
+pub fn date2epoch(x: &NaiveDate, epoch: Epoch) -> f64 {
+    let days = (x.num_days_from_ce() - R_DATE_FROM_CE) as f64;
+    let months = (x.year() as f64 - 1960.0) * 12.0 + x.month0() as f64;
+    match epoch {
+        Epoch::Sas | Epoch::StataTd => days + SAS_TO_R_DAYS as f64,
+        Epoch::StataTm => months,
+        Epoch::StataTq => (months / 3.0).floor(),
+        Epoch::Spss => (days + SPSS_TO_R_DAYS as f64) * SECS_PER_DAY,
+        Epoch::Matlab => days + MATLAB_TO_R_DAYS as f64,
+    }
+}
+
+// the units of Unix timestamps per second
+pub fn to_unit(x: &str) -> Option<i64> {
+    match x {
+        "s" => Some(1),
+        "ms" => Some(1_000),
+        "us" => Some(1_000_000),
+        "ns" => Some(1_000_000_000),
+        _ => None,
+    }
+}
+
+// the date of the Unix timestamp (in seconds) at the UTC offset (in seconds)
+pub fn unix2date(secs: f64, offset: i32) -> Option<NaiveDate> {
+    real_days_since_epoch_to_date((secs + offset as f64) / SECS_PER_DAY)
+}
+
+pub fn is_posix(x: &Robj) -> bool {
+    x.inherits("POSIXct") || x.inherits("POSIXlt")
+}
+
+// the integer field of POSIXlt, e.g., "year", "mon" or "mday"
+fn posixlt_field(x: &Robj, name: &str) -> extendr_api::Result<Robj> {
+    call!("as.integer", call!("[[", x, name)?)
+}
+
+// the calendar dates of POSIXct or POSIXlt in the timezone tz, or in the
+// timezone of the object (its "tzone" attribute) when tz is empty
+pub fn posix2date(x: &Robj, tz: &str) -> extendr_api::Result<Vec<Option<NaiveDate>>> {
+    let lt = if tz.is_empty() {
+        if x.inherits("POSIXlt") {
+            x.clone()
+        } else {
+            call!("as.POSIXlt", x)?
+        }
+    } else {
+        call!("as.POSIXlt", call!("as.POSIXct", x)?, tz = tz)?
+    };
+    let lt = call!("unclass", lt)?;
+    let (year, mon, mday) = (
+        posixlt_field(&lt, "year")?,
+        posixlt_field(&lt, "mon")?,
+        posixlt_field(&lt, "mday")?,
+    );
+    let out = year
+        .as_integer_slice()
+        .unwrap()
+        .iter()
+        .zip(mon.as_integer_slice().unwrap())
+        .zip(mday.as_integer_slice().unwrap())
+        .map(|((y, m), d)| {
+            if y.is_na() || m.is_na() || d.is_na() {
+                None
+            } else {
+                // years since 1900 and zero-based months
+                NaiveDate::from_ymd_opt(y + 1900, (m + 1) as u32, *d as u32)
+            }
+        })
+        .collect();
+    Ok(out)
+}
+
+// Date, or POSIXct and POSIXlt whose dates are taken in their own timezones
 pub fn robj2date(x: Robj, var: &str) -> extendr_api::Result<Vec<Option<NaiveDate>>> {
+    if is_posix(&x) {
+        return posix2date(&x, "");
+    }
     if !x.inherits("Date") {
         return Err(Error::Other(format!("{} is not a Date", var)));
     }
@@ -88,13 +226,76 @@ impl ToRDate for [f64] {
     }
 }
 
+// integer-backed Date, where i32::MIN is NA_integer_
 impl ToRDate for [i32] {
     fn to_rdate(&self) -> Robj {
-        let out: Vec<f64> = self.iter().map(|v| *v as f64).collect();
-        r!(out).set_class(&["Date"]).unwrap().clone()
+        r!(self).set_class(&["Date"]).unwrap().clone()
+    }
+}
+
+// the integer-backed Date if integer is true, e.g., by integer_option(), and
+// the double-backed one otherwise
+pub fn to_rdate_as(value: &[Option<NaiveDate>], integer: bool) -> Robj {
+    if integer {
+        let days: Vec<i32> = value
+            .iter()
+            .map(|v| match v {
+                Some(date) => date.num_days_from_ce() - R_DATE_FROM_CE,
+                None => i32::na(),
+            })
+            .collect();
+        days.to_rdate()
+    } else {
+        value.to_rdate()
     }
 }
 
+// whether the option ymd.integer_date is TRUE
+pub fn integer_option() -> bool {
+    call!("getOption", "ymd.integer_date", false)
+        .ok()
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+// the Date result of the input x, which keeps the names, dim and dimnames of x;
+// IDate (data.table's integer-backed Date) input returns IDate, and the Date is
+// integer-backed as well if the option ymd.integer_date is TRUE
+pub fn dates_like(value: &[Option<NaiveDate>], x: &Robj) -> Robj {
+    let idate = x.inherits("IDate");
+    let mut out = to_rdate_as(value, idate || integer_option());
+    if idate {
+        out.set_class(&["IDate", "Date"]).unwrap();
+    }
+    if x.len() != value.len() && !x.inherits("POSIXlt") {
+        return out;
+    }
+    // POSIXlt is a list of the fields whose names are not the element names
+    let names = if x.inherits("POSIXlt") {
+        call!("names", x).ok().filter(|v| !v.is_null())
+    } else {
+        x.get_attrib("names")
+    };
+    if let Some(names) = names {
+        out.set_attrib("names", names).unwrap();
+    }
+    for name in ["dim", "dimnames"] {
+        if let Some(attr) = x.get_attrib(name) {
+            out.set_attrib(name, attr).unwrap();
+        }
+    }
+    out
+}
+
+// the Date input x returned as it is, unless the option ymd.integer_date asks
+// for the integer storage
+pub fn date_as_is(x: Robj) -> Robj {
+    if !integer_option() || x.rtype() == Rtype::Integers {
+        return x;
+    }
+    dates_like(&robj2date(x.clone(), "x").unwrap(), &x)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +317,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn to_date_attrs() {
+        test! {
+            single_threaded(|| {
+                let date = NaiveDate::from_ymd_opt(2021, 12, 29);
+                assert_eq!([18990, i32::na()].to_rdate(), r!([18990, i32::na()]).set_class(&["Date"]).unwrap().clone());
+                let x: Robj = r!(["a", "b"]).set_attrib("names", r!(["x", "y"])).unwrap().clone();
+                let out = dates_like(&[date, None], &x);
+                assert_eq!(out.rtype(), Rtype::Doubles);
+                assert_eq!(out.get_attrib("names"), Some(r!(["x", "y"])));
+                let x: Robj = r!([1, 2, 3, 4]).set_attrib("dim", r!([2, 2])).unwrap().clone();
+                let out = dates_like(&[date; 4], &x);
+                assert_eq!(out.get_attrib("dim"), Some(r!([2, 2])));
+                R!("options(ymd.integer_date = TRUE)").unwrap();
+                let out = dates_like(&[date, None], &r!(["a", "b"]));
+                assert_eq!(out, r!([18990, i32::na()]).set_class(&["Date"]).unwrap().clone());
+                R!("options(ymd.integer_date = NULL)").unwrap();
+                let x: Robj = r!([1, 2]).set_class(&["IDate", "Date"]).unwrap().clone();
+                let out = dates_like(&[date, None], &x);
+                assert_eq!(out, r!([18990, i32::na()]).set_class(&["IDate", "Date"]).unwrap().clone());
+            });
+        }
+    }
+
+    #[test]
+    fn posix_to_date() {
+        test! {
+            single_threaded(|| {
+                // 2021-12-26 00:00:00 UTC
+                let ct: Robj = r!([1640476800.0, f64::na()]).set_class(&["POSIXct", "POSIXt"]).unwrap().set_attrib("tzone", "UTC").unwrap().clone();
+                let date = NaiveDate::from_ymd_opt(2021, 12, 26);
+                assert_eq!(robj2date(ct.clone(), "ct").unwrap(), [date, None]);
+                assert_eq!(posix2date(&ct, "America/New_York").unwrap(), [date.unwrap().pred_opt(), None]);
+                let lt = call!("as.POSIXlt", &ct, tz = "Asia/Shanghai").unwrap();
+                assert_eq!(robj2date(lt.clone(), "lt").unwrap(), [date, None]);
+                assert_eq!(posix2date(&lt, "America/New_York").unwrap(), [date.unwrap().pred_opt(), None]);
+            });
+        }
+    }
+
+    #[test]
+    fn epochs() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        let date = fromymd(2021, 12, 25);
+        assert_eq!(epoch2date(22639.0, Epoch::Sas), date);
+        assert_eq!(epoch2date(22639.0, Epoch::StataTd), date);
+        assert_eq!(epoch2date(0.0, Epoch::Sas), fromymd(1960, 1, 1));
+        assert_eq!(epoch2date(-1.5, Epoch::Sas), fromymd(1959, 12, 30));
+        assert_eq!(epoch2date(743.0, Epoch::StataTm), fromymd(2021, 12, 1));
+        assert_eq!(epoch2date(-1.0, Epoch::StataTm), fromymd(1959, 12, 1));
+        assert_eq!(epoch2date(247.0, Epoch::StataTq), fromymd(2021, 10, 1));
+        assert_eq!(epoch2date(-1.0, Epoch::StataTq), fromymd(1959, 10, 1));
+        assert_eq!(epoch2date(13859769600.0, Epoch::Spss), date);
+        assert_eq!(epoch2date(13859769600.0 + 3600.0, Epoch::Spss), date);
+        assert_eq!(epoch2date(0.0, Epoch::Spss), fromymd(1582, 10, 14));
+        assert_eq!(epoch2date(738515.0, Epoch::Matlab), date);
+        assert_eq!(epoch2date(738515.5, Epoch::Matlab), date);
+        assert_eq!(epoch2date(1.0, Epoch::Matlab), fromymd(0, 1, 1));
+        for epoch in [
+            Epoch::Sas,
+            Epoch::StataTm,
+            Epoch::StataTq,
+            Epoch::Spss,
+            Epoch::Matlab,
+        ] {
+            assert_eq!(epoch2date(f64::NAN, epoch), None);
+            assert_eq!(epoch2date(f64::INFINITY, epoch), None);
+            assert_eq!(epoch2date(1e300, epoch), None);
+            assert_eq!(epoch2date(-1e300, epoch), None);
+        }
+        assert_eq!(epoch2date(2e9, Epoch::StataTm), None);
+
+        let date = date.unwrap();
+        assert_eq!(date2epoch(&date, Epoch::Sas), 22639.0);
+        assert_eq!(date2epoch(&date, Epoch::StataTm), 743.0);
+        assert_eq!(date2epoch(&date, Epoch::StataTq), 247.0);
+        assert_eq!(date2epoch(&date, Epoch::Spss), 13859769600.0);
+        assert_eq!(date2epoch(&date, Epoch::Matlab), 738515.0);
+        assert_eq!(
+            date2epoch(&fromymd(1959, 12, 31).unwrap(), Epoch::StataTq),
+            -1.0
+        );
+        for days in -100000..100000 {
+            let date = NaiveDate::from_num_days_from_ce_opt(R_DATE_FROM_CE + days).unwrap();
+            for epoch in [Epoch::Sas, Epoch::Spss, Epoch::Matlab] {
+                assert_eq!(epoch2date(date2epoch(&date, epoch), epoch), Some(date));
+            }
+        }
+        for months in -10000..10000 {
+            let date = epoch2date(months as f64, Epoch::StataTm).unwrap();
+            assert_eq!(date2epoch(&date, Epoch::StataTm), months as f64);
+            let date = epoch2date(months as f64, Epoch::StataTq).unwrap();
+            assert_eq!(date2epoch(&date, Epoch::StataTq), months as f64);
+        }
+    }
+
+    #[test]
+    fn unix_timestamps() {
+        let fromymd = NaiveDate::from_ymd_opt;
+        assert_eq!(unix2date(1640390400.0, 0), fromymd(2021, 12, 25));
+        assert_eq!(unix2date(1640476799.5, 0), fromymd(2021, 12, 25));
+        assert_eq!(unix2date(1640390400.0, -3600), fromymd(2021, 12, 24));
+        assert_eq!(unix2date(1640390400.0, 8 * 3600), fromymd(2021, 12, 25));
+        assert_eq!(unix2date(-1.0, 0), fromymd(1969, 12, 31));
+        assert_eq!(unix2date(f64::NAN, 0), None);
+        assert_eq!(unix2date(f64::INFINITY, 0), None);
+        assert_eq!(unix2date(1e300, 0), None);
+        assert_eq!(unix2date(-1e300, 0), None);
+        assert_eq!(to_unit("ms"), Some(1000));
+        assert_eq!(to_unit("m"), None);
+    }
+
     #[test]
     fn floor_fractional_days() {
         assert_eq!(